@@ -161,6 +161,7 @@ impl AssetGraphBuilder {
         AssetRequest {
           file_path: path,
           code: code.clone(),
+          is_entry: dependency.is_entry,
           pipeline: pipeline.clone(),
           side_effects,
           env: dependency.env.clone(),
@@ -233,6 +234,7 @@ impl AssetGraphBuilder {
     let AssetRequestOutput {
       asset,
       dependencies,
+      ..
     } = result;
     let incoming_dep_node_index = *self
       .request_id_to_dep_node_index
@@ -13,6 +13,7 @@ use parcel_core::plugin::TransformationInput;
 use parcel_core::types::Asset;
 use parcel_core::types::AssetStats;
 use parcel_core::types::Dependency;
+use parcel_core::types::Diagnostic;
 use parcel_core::types::Environment;
 use parcel_core::types::FileType;
 
@@ -32,6 +33,7 @@ pub struct AssetRequest {
   pub env: Arc<Environment>,
   pub file_path: PathBuf,
   pub code: Option<String>,
+  pub is_entry: bool,
   pub pipeline: Option<String>,
   pub side_effects: bool,
   pub query: Option<String>,
@@ -41,6 +43,8 @@ pub struct AssetRequest {
 pub struct AssetRequestOutput {
   pub asset: Asset,
   pub dependencies: Vec<Dependency>,
+  /// Warnings and other non-fatal diagnostics that the transformers found
+  pub diagnostics: Vec<Diagnostic>,
 }
 
 impl Request for AssetRequest {
@@ -78,6 +82,7 @@ impl Request for AssetRequest {
         file_path: self.file_path.clone(),
         code: self.code.clone(),
         env: self.env.clone(),
+        is_entry: self.is_entry,
         side_effects: self.side_effects,
       }),
       asset_type,
@@ -95,6 +100,7 @@ impl Request for AssetRequest {
           ..result.asset
         },
         dependencies: result.dependencies,
+        diagnostics: result.diagnostics,
       }),
       // TODO: Support invalidations
       invalidations: vec![],
@@ -110,6 +116,10 @@ fn run_pipeline(
   transform_ctx: &mut RunTransformContext,
 ) -> anyhow::Result<TransformResult> {
   let mut dependencies = vec![];
+  let mut diagnostics = vec![];
+  let mut extracted_css = vec![];
+  let mut i18n_keys = vec![];
+  let mut injected_helper_ids = vec![];
   let mut invalidations = vec![];
 
   let mut transform_input = input;
@@ -137,6 +147,10 @@ fn run_pipeline(
     }
 
     dependencies.extend(transform_result.dependencies);
+    diagnostics.extend(transform_result.diagnostics);
+    extracted_css.extend(transform_result.extracted_css);
+    i18n_keys.extend(transform_result.i18n_keys);
+    injected_helper_ids.extend(transform_result.injected_helper_ids);
     invalidations.extend(transform_result.invalidate_on_file_change);
   }
 
@@ -144,9 +158,55 @@ fn run_pipeline(
     Ok(TransformResult {
       asset,
       dependencies,
+      diagnostics,
+      extracted_css,
+      i18n_keys,
+      injected_helper_ids,
       invalidate_on_file_change: invalidations,
     })
   } else {
     Err(diagnostic_error!("No transformations for Asset"))
   }
 }
+
+#[cfg(test)]
+mod test {
+  use parcel_core::types::{DiagnosticSeverity, ParcelOptions};
+
+  use crate::requests::RequestResult;
+  use crate::test_utils::{request_tracker, RequestTrackerTestOptions};
+
+  use super::*;
+
+  #[test]
+  fn test_asset_request_returns_the_transformer_diagnostics() {
+    let mut request_tracker = request_tracker(RequestTrackerTestOptions {
+      parcel_options: ParcelOptions {
+        report_unused_imports: true,
+        ..ParcelOptions::default()
+      },
+      ..RequestTrackerTestOptions::default()
+    });
+
+    let asset_request = AssetRequest {
+      env: Arc::new(Environment::default()),
+      file_path: PathBuf::from("/project/index.js"),
+      code: Some(String::from("import { used, unused } from 'x';\nused();")),
+      is_entry: true,
+      pipeline: None,
+      side_effects: false,
+      query: None,
+    };
+
+    let RequestResult::Asset(output) = request_tracker.run_request(asset_request).unwrap() else {
+      panic!("Got invalid result");
+    };
+
+    assert_eq!(output.diagnostics.len(), 1);
+    assert_eq!(output.diagnostics[0].severity, DiagnosticSeverity::Info);
+    assert_eq!(
+      output.diagnostics[0].message,
+      "`unused` is imported from 'x' but never used."
+    );
+  }
+}
@@ -388,6 +388,7 @@ impl TargetRequest {
             .source_maps
             .then(|| TargetSourceMapOptions::default()),
           source_type: SourceType::Module,
+          target_name: Some(String::from("default")),
         }),
         loc: None,
         name: String::from("default"),
@@ -526,6 +527,7 @@ impl TargetRequest {
             SourceMapField::Options(source_maps) => Some(source_maps.clone()),
           }),
         },
+        target_name: Some(String::from(target_name)),
         ..Environment::default()
       }),
       loc: None, // TODO
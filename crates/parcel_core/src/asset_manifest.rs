@@ -0,0 +1,170 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::plugin::TransformResult;
+use crate::types::{Asset, FileType};
+
+/// A serializable overview of transformed assets and the dependencies between them
+///
+/// Unlike the [`AssetGraph`](crate::asset_graph::AssetGraph) built during a build, this only
+/// records what is needed to visualize the graph, so it can be written to disk as a manifest.
+///
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetManifest {
+  pub nodes: Vec<AssetManifestNode>,
+  pub edges: Vec<AssetManifestEdge>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetManifestNode {
+  /// The unique key of the asset, or its id when it has no unique key
+  pub key: String,
+  pub asset_type: FileType,
+  pub file_path: PathBuf,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetManifestEdge {
+  /// The key of the asset that has the dependency
+  pub from: String,
+  /// The key of the asset the specifier points to, if it is part of the results
+  pub to: Option<String>,
+  pub specifier: String,
+}
+
+/// Build an [`AssetManifest`] from the results of transforming a set of assets
+///
+/// Specifiers are matched against the paths of the other assets relative to the asset that has the
+/// dependency, with or without an extension. Specifiers that do not match any of the assets, such
+/// as bare package specifiers, are kept as edges without a target.
+///
+pub fn build_asset_graph(results: &[TransformResult]) -> AssetManifest {
+  let nodes: Vec<AssetManifestNode> = results
+    .iter()
+    .map(|result| AssetManifestNode {
+      key: asset_key(&result.asset),
+      asset_type: result.asset.asset_type.clone(),
+      file_path: result.asset.file_path.clone(),
+    })
+    .collect();
+
+  let edges = results
+    .iter()
+    .zip(nodes.iter())
+    .flat_map(|(result, node)| {
+      result
+        .dependencies
+        .iter()
+        .map(|dependency| AssetManifestEdge {
+          from: node.key.clone(),
+          to: find_target(&nodes, &node.file_path, &dependency.specifier),
+          specifier: dependency.specifier.clone(),
+        })
+    })
+    .collect();
+
+  AssetManifest { nodes, edges }
+}
+
+fn asset_key(asset: &Asset) -> String {
+  asset
+    .unique_key
+    .clone()
+    .unwrap_or_else(|| asset.id().to_string())
+}
+
+fn find_target(nodes: &[AssetManifestNode], from: &Path, specifier: &str) -> Option<String> {
+  let target = from.parent()?.join(specifier);
+
+  nodes
+    .iter()
+    .find(|node| node.file_path == target || node.file_path.with_extension("") == target)
+    .map(|node| node.key.clone())
+}
+
+#[cfg(test)]
+mod test {
+  use crate::types::Dependency;
+
+  use super::*;
+
+  #[test]
+  fn test_build_asset_graph() {
+    let results = vec![
+      TransformResult {
+        asset: Asset {
+          file_path: PathBuf::from("/project/index.js"),
+          unique_key: Some(String::from("index")),
+          ..Asset::default()
+        },
+        dependencies: vec![Dependency {
+          source_path: Some(PathBuf::from("/project/index.js")),
+          specifier: String::from("./other"),
+          ..Dependency::default()
+        }],
+        ..TransformResult::default()
+      },
+      TransformResult {
+        asset: Asset {
+          file_path: PathBuf::from("/project/other.js"),
+          unique_key: Some(String::from("other")),
+          ..Asset::default()
+        },
+        ..TransformResult::default()
+      },
+    ];
+
+    assert_eq!(
+      build_asset_graph(&results),
+      AssetManifest {
+        nodes: vec![
+          AssetManifestNode {
+            key: String::from("index"),
+            asset_type: FileType::Js,
+            file_path: PathBuf::from("/project/index.js"),
+          },
+          AssetManifestNode {
+            key: String::from("other"),
+            asset_type: FileType::Js,
+            file_path: PathBuf::from("/project/other.js"),
+          },
+        ],
+        edges: vec![AssetManifestEdge {
+          from: String::from("index"),
+          to: Some(String::from("other")),
+          specifier: String::from("./other"),
+        }],
+      }
+    );
+  }
+
+  #[test]
+  fn test_build_asset_graph_keeps_unresolved_specifiers() {
+    let results = vec![TransformResult {
+      asset: Asset {
+        file_path: PathBuf::from("/project/index.js"),
+        unique_key: Some(String::from("index")),
+        ..Asset::default()
+      },
+      dependencies: vec![Dependency {
+        source_path: Some(PathBuf::from("/project/index.js")),
+        specifier: String::from("react"),
+        ..Dependency::default()
+      }],
+      ..TransformResult::default()
+    }];
+
+    assert_eq!(
+      build_asset_graph(&results).edges,
+      vec![AssetManifestEdge {
+        from: String::from("index"),
+        to: None,
+        specifier: String::from("react"),
+      }]
+    );
+  }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::plugin::TransformResult;
+
+/// Find the assets that must be transformed again after `changed_paths` have changed
+///
+/// An asset is invalidated when its own file changed, or any of the files it listed in
+/// `invalidate_on_file_change`, such as the `tsconfig.json` it was compiled with. The ids are
+/// returned in the order of `results`.
+///
+pub fn compute_invalidated(results: &[TransformResult], changed_paths: &[PathBuf]) -> Vec<u64> {
+  let changed_paths: HashSet<&Path> = changed_paths.iter().map(PathBuf::as_path).collect();
+
+  results
+    .iter()
+    .filter(|result| {
+      changed_paths.contains(result.asset.file_path.as_path())
+        || result
+          .invalidate_on_file_change
+          .iter()
+          .any(|path| changed_paths.contains(path.as_path()))
+    })
+    .map(|result| result.asset.id())
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+  use crate::types::Asset;
+
+  use super::*;
+
+  #[test]
+  fn test_changed_config_invalidates_the_assets_that_listed_it() {
+    let results = vec![
+      TransformResult {
+        asset: Asset {
+          file_path: PathBuf::from("/project/index.ts"),
+          ..Asset::default()
+        },
+        invalidate_on_file_change: vec![PathBuf::from("/project/tsconfig.json")],
+        ..TransformResult::default()
+      },
+      TransformResult {
+        asset: Asset {
+          file_path: PathBuf::from("/project/other.js"),
+          ..Asset::default()
+        },
+        ..TransformResult::default()
+      },
+    ];
+
+    assert_eq!(
+      compute_invalidated(&results, &[PathBuf::from("/project/tsconfig.json")]),
+      vec![results[0].asset.id()]
+    );
+  }
+
+  #[test]
+  fn test_changed_source_file_invalidates_its_asset() {
+    let results = vec![
+      TransformResult {
+        asset: Asset {
+          file_path: PathBuf::from("/project/index.ts"),
+          ..Asset::default()
+        },
+        invalidate_on_file_change: vec![PathBuf::from("/project/tsconfig.json")],
+        ..TransformResult::default()
+      },
+      TransformResult {
+        asset: Asset {
+          file_path: PathBuf::from("/project/other.js"),
+          ..Asset::default()
+        },
+        ..TransformResult::default()
+      },
+    ];
+
+    assert_eq!(
+      compute_invalidated(&results, &[PathBuf::from("/project/other.js")]),
+      vec![results[1].asset.id()]
+    );
+    assert!(compute_invalidated(&results, &[PathBuf::from("/project/unrelated.js")]).is_empty());
+  }
+}
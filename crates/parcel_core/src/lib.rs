@@ -1,7 +1,11 @@
 pub mod asset_graph;
+pub mod asset_manifest;
 pub mod bundle_graph;
 pub mod cache;
 pub mod config_loader;
 pub mod hash;
+pub mod invalidation;
 pub mod plugin;
+pub mod semaphore;
 pub mod types;
+pub mod unique_key_registry;
@@ -1,10 +1,16 @@
 use std::fmt::Debug;
 use std::fs::File;
+use std::io::{Seek, SeekFrom};
 
 use crate::bundle_graph::BundleGraph;
 use crate::types::Bundle;
 use crate::types::SourceMap;
 
+pub mod noop_optimizer_plugin;
+pub mod source_map_url_optimizer_plugin;
+#[cfg(test)]
+mod test_helpers;
+
 pub struct OptimizeContext<'a> {
   pub bundle: &'a Bundle,
   pub bundle_graph: &'a BundleGraph,
@@ -18,6 +24,23 @@ pub struct OptimizedBundle {
   // TODO ast, map, type
 }
 
+/// Copy the contents of a bundle into a new temporary file, positioned at the start
+///
+/// Optimizers write their output into the copy, so that the file they were given is left
+/// untouched. A cloned `File` would share the underlying file and its cursor instead.
+pub(crate) fn copy_contents(contents: &File) -> std::io::Result<File> {
+  let mut input = contents;
+  let position = input.stream_position()?;
+
+  let mut copy = tempfile::tempfile()?;
+  input.seek(SeekFrom::Start(0))?;
+  std::io::copy(&mut input, &mut copy)?;
+  input.seek(SeekFrom::Start(position))?;
+
+  copy.seek(SeekFrom::Start(0))?;
+  Ok(copy)
+}
+
 /// Optimises a bundle
 ///
 /// Optimizers are commonly used to implement minification, tree shaking, dead code elimination,
@@ -0,0 +1,51 @@
+use crate::plugin::optimizer_plugin::copy_contents;
+use crate::plugin::{OptimizeContext, OptimizedBundle, OptimizerPlugin};
+
+/// An optimizer that returns a copy of the bundle contents unchanged
+///
+/// This is useful as a placeholder for pipelines that do not need any optimizations.
+#[derive(Debug, Default)]
+pub struct NoopOptimizerPlugin {}
+
+impl OptimizerPlugin for NoopOptimizerPlugin {
+  fn optimize(&self, ctx: OptimizeContext) -> Result<OptimizedBundle, anyhow::Error> {
+    Ok(OptimizedBundle {
+      contents: copy_contents(ctx.contents)?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::io::{Read, Seek, SeekFrom};
+
+  use crate::bundle_graph::BundleGraph;
+  use crate::plugin::optimizer_plugin::test_helpers::{make_bundle, make_contents};
+  use crate::types::FileType;
+
+  use super::*;
+
+  #[test]
+  fn test_contents_are_passed_through() {
+    let bundle = make_bundle(FileType::Js, "index.js");
+    let contents = make_contents("console.log('hello');");
+
+    let mut optimized_bundle = NoopOptimizerPlugin::default()
+      .optimize(OptimizeContext {
+        bundle: &bundle,
+        bundle_graph: &BundleGraph {},
+        contents: &contents,
+        map: None,
+      })
+      .unwrap();
+
+    let mut optimized_contents = String::new();
+    optimized_bundle.contents.seek(SeekFrom::Start(0)).unwrap();
+    optimized_bundle
+      .contents
+      .read_to_string(&mut optimized_contents)
+      .unwrap();
+
+    assert_eq!(optimized_contents, "console.log('hello');");
+  }
+}
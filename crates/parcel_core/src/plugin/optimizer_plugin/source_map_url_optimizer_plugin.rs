@@ -0,0 +1,122 @@
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+use crate::plugin::optimizer_plugin::copy_contents;
+use crate::plugin::{OptimizeContext, OptimizedBundle, OptimizerPlugin};
+use crate::types::FileType;
+
+/// An optimizer that appends a `sourceMappingURL` comment to bundles with a source map
+///
+/// The source map is expected to be written next to the bundle, using the bundle name with a
+/// `.map` extension. The comment is appended to a copy of the contents.
+#[derive(Debug, Default)]
+pub struct SourceMapUrlOptimizerPlugin {}
+
+impl OptimizerPlugin for SourceMapUrlOptimizerPlugin {
+  fn optimize(&self, ctx: OptimizeContext) -> Result<OptimizedBundle, anyhow::Error> {
+    let mut contents = copy_contents(ctx.contents)?;
+
+    let file_name = ctx
+      .bundle
+      .name
+      .as_ref()
+      .and_then(|name| Path::new(name).file_name())
+      .map(|file_name| file_name.to_string_lossy());
+
+    if let (Some(file_name), Some(_map)) = (file_name, ctx.map) {
+      let url = format!("{}.map", file_name);
+
+      contents.seek(SeekFrom::End(0))?;
+      match ctx.bundle.bundle_type {
+        FileType::Css => write!(contents, "\n/*# sourceMappingURL={} */", url)?,
+        _ => write!(contents, "\n//# sourceMappingURL={}", url)?,
+      }
+    }
+
+    Ok(OptimizedBundle { contents })
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::io::Read;
+
+  use crate::bundle_graph::BundleGraph;
+  use crate::plugin::optimizer_plugin::test_helpers::{make_bundle, make_contents};
+  use crate::types::{Bundle, SourceMap};
+
+  use super::*;
+
+  fn optimize(bundle: &Bundle, contents: &str, map: Option<&SourceMap>) -> String {
+    let contents = make_contents(contents);
+
+    let mut optimized_bundle = SourceMapUrlOptimizerPlugin::default()
+      .optimize(OptimizeContext {
+        bundle,
+        bundle_graph: &BundleGraph {},
+        contents: &contents,
+        map,
+      })
+      .unwrap();
+
+    let mut optimized_contents = String::new();
+    optimized_bundle.contents.seek(SeekFrom::Start(0)).unwrap();
+    optimized_bundle
+      .contents
+      .read_to_string(&mut optimized_contents)
+      .unwrap();
+
+    optimized_contents
+  }
+
+  #[test]
+  fn test_appends_source_map_comment_to_js_bundles() {
+    let bundle = make_bundle(FileType::Js, "dist/index.js");
+
+    assert_eq!(
+      optimize(&bundle, "console.log('hello');", Some(&SourceMap {})),
+      "console.log('hello');\n//# sourceMappingURL=index.js.map"
+    );
+  }
+
+  #[test]
+  fn test_appends_source_map_comment_to_css_bundles() {
+    let bundle = make_bundle(FileType::Css, "dist/index.css");
+
+    assert_eq!(
+      optimize(&bundle, "body {}", Some(&SourceMap {})),
+      "body {}\n/*# sourceMappingURL=index.css.map */"
+    );
+  }
+
+  #[test]
+  fn test_skips_bundles_without_source_map() {
+    let bundle = make_bundle(FileType::Js, "dist/other.js");
+
+    assert_eq!(
+      optimize(&bundle, "console.log('hello');", None),
+      "console.log('hello');"
+    );
+  }
+
+  #[test]
+  fn test_does_not_modify_the_input_contents() {
+    let bundle = make_bundle(FileType::Js, "dist/index.js");
+    let mut contents = make_contents("console.log('hello');");
+
+    SourceMapUrlOptimizerPlugin::default()
+      .optimize(OptimizeContext {
+        bundle: &bundle,
+        bundle_graph: &BundleGraph {},
+        contents: &contents,
+        map: Some(&SourceMap {}),
+      })
+      .unwrap();
+
+    let mut input_contents = String::new();
+    contents.seek(SeekFrom::Start(0)).unwrap();
+    contents.read_to_string(&mut input_contents).unwrap();
+
+    assert_eq!(input_contents, "console.log('hello');");
+  }
+}
@@ -0,0 +1,35 @@
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+
+use crate::types::{Bundle, BundleBehavior, Environment, FileType, Target};
+
+/// Create a bundle with the given type and name for testing
+pub(crate) fn make_bundle(bundle_type: FileType, name: &str) -> Bundle {
+  Bundle {
+    bundle_behavior: BundleBehavior::None,
+    bundle_type,
+    entry_asset_ids: Vec::new(),
+    env: Environment::default(),
+    hash_reference: String::default(),
+    id: name.replace('/', "_"),
+    is_splittable: true,
+    main_entry_id: None,
+    manual_shared_bundle: None,
+    name: Some(String::from(name)),
+    needs_stable_name: false,
+    pipeline: None,
+    public_id: None,
+    target: Target::default(),
+  }
+}
+
+/// Write `contents` into a temporary file that can be read from and written to
+///
+/// The file is removed once it is closed.
+pub(crate) fn make_contents(contents: &str) -> File {
+  let mut file = tempfile::tempfile().unwrap();
+
+  file.write_all(contents.as_bytes()).unwrap();
+  file.seek(SeekFrom::Start(0)).unwrap();
+  file
+}
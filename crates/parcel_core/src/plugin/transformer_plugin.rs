@@ -1,13 +1,24 @@
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 use serde::Serialize;
 
 use parcel_filesystem::os_file_system::OsFileSystem;
 use parcel_filesystem::FileSystemRef;
 
-use crate::types::{Asset, Code, Dependency, Environment, ParcelOptions, SpecifierType};
+use crate::config_loader::ConfigLoader;
+use crate::diagnostic_error;
+use crate::hash::hash_string;
+use crate::semaphore::Semaphore;
+use crate::types::{
+  Asset, Code, CodeFrame, Dependency, Diagnostic, DiagnosticBuilder, Environment, ParcelOptions,
+  SourceLocation, SourceType, SpecifierType,
+};
 
 pub struct ResolveOptions {
   /// A list of custom conditions to use when resolving package.json "exports" and "imports"
@@ -27,26 +38,108 @@ pub struct InitialAsset {
   /// It is not set in most cases but should be respected when present.
   pub code: Option<String>,
   pub env: Arc<Environment>,
+  /// Whether the asset was requested by an entry dependency, and is therefore an entry of the build
+  pub is_entry: bool,
   pub side_effects: bool,
 }
 
+/// A script that is inlined within another file, such as a `<script type="module">` in HTML
+pub struct InlineScript {
+  pub code: String,
+  pub env: Arc<Environment>,
+  /// The position of the script within its parent, to tell apart the scripts of the same file
+  pub index: usize,
+  pub parent_path: PathBuf,
+  pub source_type: SourceType,
+  file_path: PathBuf,
+}
+
+impl InlineScript {
+  pub fn new(
+    parent_path: PathBuf,
+    index: usize,
+    code: String,
+    env: Arc<Environment>,
+    source_type: SourceType,
+  ) -> Self {
+    // The synthetic path sits next to the parent, so that relative specifiers resolve the same
+    let file_name = parent_path
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_default();
+    let file_path = parent_path.with_file_name(format!("{}.{}.js", file_name, index));
+
+    Self {
+      code,
+      env,
+      index,
+      parent_path,
+      source_type,
+      file_path,
+    }
+  }
+
+  /// A key derived from the parent path and index, which is stable across builds
+  ///
+  /// The parent path is taken relative to `project_root`, so that the key is the same across
+  /// checkouts of the project.
+  pub fn unique_key(&self, project_root: &Path) -> String {
+    let parent_path = self
+      .parent_path
+      .strip_prefix(project_root)
+      .unwrap_or(&self.parent_path);
+
+    hash_string(format!("{}:{}", parent_path.display(), self.index))
+  }
+}
+
 /// The input to transform within the plugin
 ///
-/// Transformers may run against two distinguished scenarios:
+/// Transformers may run against three distinguished scenarios:
 ///
 /// * InitialAsset that have just been discovered
 /// * Outputs of previous transformation steps, which are in-place modified
+/// * Scripts that are inlined within another file, which have a synthetic path
+///
+/// Either of these may be wrapped with an environment override, which takes precedence over the
+/// environment of the input itself.
 ///
 pub enum TransformationInput {
   InitialAsset(InitialAsset),
   Asset(Asset),
+  InlineScript(InlineScript),
+  EnvOverride(Box<TransformationInput>, Arc<Environment>),
 }
 
 impl TransformationInput {
+  /// Transform this input with `env` instead of its own environment
+  ///
+  /// This is useful to re-transform an input with a modified environment, for example to force a
+  /// different output format.
+  pub fn with_env_override(self, env: Arc<Environment>) -> Self {
+    match self {
+      TransformationInput::EnvOverride(input, _) => TransformationInput::EnvOverride(input, env),
+      input => TransformationInput::EnvOverride(Box::new(input), env),
+    }
+  }
+
+  /// The environment that overrides the one of the input, if any
+  pub fn env_override(&self) -> Option<Arc<Environment>> {
+    match self {
+      TransformationInput::EnvOverride(_, env) => Some(env.clone()),
+      _ => None,
+    }
+  }
+
   pub fn env(&self) -> Arc<Environment> {
     match self {
       TransformationInput::InitialAsset(raw_asset) => raw_asset.env.clone(),
       TransformationInput::Asset(asset) => asset.env.clone(),
+      TransformationInput::InlineScript(script) => Arc::new(Environment {
+        source_type: script.source_type,
+        ..(*script.env).clone()
+      }),
+      TransformationInput::EnvOverride(_, env) => env.clone(),
     }
   }
 
@@ -54,6 +147,8 @@ impl TransformationInput {
     match self {
       TransformationInput::InitialAsset(raw_asset) => raw_asset.file_path.as_path(),
       TransformationInput::Asset(asset) => &asset.file_path,
+      TransformationInput::InlineScript(script) => &script.file_path,
+      TransformationInput::EnvOverride(input, _) => input.file_path(),
     }
   }
 
@@ -69,6 +164,21 @@ impl TransformationInput {
         Ok(Arc::new(code))
       }
       TransformationInput::Asset(asset) => Ok(asset.code.clone()),
+      TransformationInput::InlineScript(script) => Ok(Arc::new(Code::from(script.code.clone()))),
+      TransformationInput::EnvOverride(input, _) => input.read_code(fs),
+    }
+  }
+
+  /// Whether the input is an entry of the build
+  ///
+  /// Assets produced by previous transformation steps are never considered entries, as any
+  /// entry-specific handling has already been applied by the first transformer.
+  pub fn is_entry(&self) -> bool {
+    match self {
+      TransformationInput::InitialAsset(raw_asset) => raw_asset.is_entry,
+      TransformationInput::Asset(_) => false,
+      TransformationInput::InlineScript(_) => false,
+      TransformationInput::EnvOverride(input, _) => input.is_entry(),
     }
   }
 
@@ -76,23 +186,48 @@ impl TransformationInput {
     match self {
       TransformationInput::InitialAsset(raw_asset) => raw_asset.side_effects,
       TransformationInput::Asset(asset) => asset.side_effects,
+      TransformationInput::InlineScript(_) => true,
+      TransformationInput::EnvOverride(input, _) => input.side_effects(),
+    }
+  }
+
+  /// The unique key of the asset, which tells apart assets that share a file path
+  pub fn unique_key(&self, project_root: &Path) -> Option<String> {
+    match self {
+      TransformationInput::InitialAsset(_) => None,
+      TransformationInput::Asset(asset) => asset.unique_key.clone(),
+      TransformationInput::InlineScript(script) => Some(script.unique_key(project_root)),
+      TransformationInput::EnvOverride(input, _) => input.unique_key(project_root),
     }
   }
 }
 
+/// A function that is applied to the code emitted by a transformer, such as to add a header
+pub type CodePostprocessor = Arc<dyn Fn(String) -> String + Send + Sync>;
+
 /// Context parameters for the transformer, other than the input.
 pub struct RunTransformContext {
+  code_postprocessor: Option<CodePostprocessor>,
+  config_file_system: Option<FileSystemRef>,
+  diagnostics_tx: Option<Sender<Diagnostic>>,
   file_system: FileSystemRef,
   options: Arc<ParcelOptions>,
   project_root: PathBuf,
+  timeout: Option<Duration>,
+  transform_semaphore: Option<Arc<Semaphore>>,
 }
 
 impl Default for RunTransformContext {
   fn default() -> Self {
     Self {
+      code_postprocessor: None,
+      config_file_system: None,
+      diagnostics_tx: None,
       file_system: Arc::new(OsFileSystem::default()),
       options: Arc::new(ParcelOptions::default()),
       project_root: PathBuf::default(),
+      timeout: None,
+      transform_semaphore: None,
     }
   }
 }
@@ -104,9 +239,68 @@ impl RunTransformContext {
     project_root: PathBuf,
   ) -> Self {
     Self {
+      code_postprocessor: None,
+      config_file_system: None,
+      diagnostics_tx: None,
       file_system,
       options,
       project_root,
+      timeout: None,
+      transform_semaphore: None,
+    }
+  }
+
+  /// Apply `code_postprocessor` to the emitted code, for textual fixups such as wrapping it
+  ///
+  /// This runs after the source map has been generated, which is not updated to match. Changes
+  /// that add lines before the original code will shift its mappings.
+  pub fn with_code_postprocessor(mut self, code_postprocessor: CodePostprocessor) -> Self {
+    self.code_postprocessor = Some(code_postprocessor);
+    self
+  }
+
+  /// Look up config files, such as `tsconfig.json` or `.browserslistrc`, in `file_system`
+  ///
+  /// Source code is still read from the main file-system of the context.
+  pub fn with_config_file_system(mut self, file_system: FileSystemRef) -> Self {
+    self.config_file_system = Some(file_system);
+    self
+  }
+
+  /// Stream diagnostics to `diagnostics_tx` as soon as transformers find them
+  ///
+  /// The diagnostics are still listed in the result of the transformation.
+  pub fn with_diagnostics_sender(mut self, diagnostics_tx: Sender<Diagnostic>) -> Self {
+    self.diagnostics_tx = Some(diagnostics_tx);
+    self
+  }
+
+  /// Transform with `options`, rather than the default options
+  pub fn with_options(mut self, options: Arc<ParcelOptions>) -> Self {
+    self.options = options;
+    self
+  }
+
+  /// Bound the number of heavy transforms that can run concurrently with this context
+  pub fn with_transform_semaphore(mut self, semaphore: Arc<Semaphore>) -> Self {
+    self.transform_semaphore = Some(semaphore);
+    self
+  }
+
+  /// Fail transforms that take longer than `timeout`, to bound latency in watch and serve modes
+  ///
+  /// This applies to the work transformers run with [`RunTransformContext::run_with_timeout`].
+  pub fn with_timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = Some(timeout);
+    self
+  }
+
+  /// Send a diagnostic to the diagnostics channel, if there is one
+  ///
+  /// Diagnostics are dropped if the receiving end has hung up, as they are part of the result too.
+  pub fn report_diagnostic(&self, diagnostic: &Diagnostic) {
+    if let Some(diagnostics_tx) = &self.diagnostics_tx {
+      let _ = diagnostics_tx.send(diagnostic.clone());
     }
   }
 
@@ -114,6 +308,28 @@ impl RunTransformContext {
     self.file_system.clone()
   }
 
+  /// The function to apply to the emitted code, when one is set
+  pub fn code_postprocessor(&self) -> Option<&CodePostprocessor> {
+    self.code_postprocessor.as_ref()
+  }
+
+  /// The file-system to look up config files in, which defaults to the main file-system
+  pub fn config_file_system(&self) -> FileSystemRef {
+    self
+      .config_file_system
+      .clone()
+      .unwrap_or_else(|| self.file_system.clone())
+  }
+
+  /// A loader for the config files that apply to `search_path`, within the project root
+  pub fn config_loader(&self, search_path: PathBuf) -> ConfigLoader {
+    ConfigLoader {
+      fs: self.config_file_system(),
+      project_root: self.project_root.clone(),
+      search_path,
+    }
+  }
+
   pub fn options(&self) -> &Arc<ParcelOptions> {
     &self.options
   }
@@ -121,17 +337,233 @@ impl RunTransformContext {
   pub fn project_root(&self) -> &Path {
     &self.project_root
   }
+
+  /// A semaphore that bounds the expensive work of transformers, when one is set
+  ///
+  /// [`RunTransformContext::run_with_timeout`] holds a permit while the work runs.
+  pub fn transform_semaphore(&self) -> Option<&Semaphore> {
+    self.transform_semaphore.as_deref()
+  }
+
+  /// The time transforms are allowed to take, when one is set
+  pub fn timeout(&self) -> Option<Duration> {
+    self.timeout
+  }
+
+  /// Run the expensive work of transforming `file_path`, failing if it exceeds the timeout
+  ///
+  /// Without a timeout the work runs on the current thread. Otherwise it runs on a worker thread,
+  /// and an error diagnostic is returned once the timeout has passed. The worker thread cannot be
+  /// interrupted, so it keeps running in the background until the work completes.
+  ///
+  /// A permit of the transform semaphore is held until the work completes, even after a timeout.
+  pub fn run_with_timeout<T: Send + 'static>(
+    &self,
+    file_path: &Path,
+    work: impl FnOnce() -> T + Send + 'static,
+  ) -> anyhow::Result<T> {
+    let permit = self
+      .transform_semaphore
+      .clone()
+      .map(Semaphore::acquire_owned);
+
+    let Some(timeout) = self.timeout else {
+      let result = work();
+      drop(permit);
+      return Ok(result);
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let worker = thread::spawn(move || {
+      let result = work();
+      drop(permit);
+      let _ = tx.send(result);
+    });
+
+    match rx.recv_timeout(timeout) {
+      Ok(result) => Ok(result),
+      Err(RecvTimeoutError::Timeout) => Err(diagnostic_error!(DiagnosticBuilder::default()
+        .code_frames(vec![CodeFrame::from(file_path.to_path_buf())])
+        .message(format!(
+          "Transforming {} took longer than the timeout of {}ms.",
+          file_path.display(),
+          timeout.as_millis()
+        )))),
+      // The worker panicked before sending its result
+      Err(RecvTimeoutError::Disconnected) => match worker.join() {
+        Err(panic) => std::panic::resume_unwind(panic),
+        Ok(()) => unreachable!("the worker always sends a result when it does not panic"),
+      },
+    }
+  }
 }
 
-#[derive(Debug, Serialize, PartialEq)]
+#[derive(Debug, Default, Serialize, PartialEq)]
 pub struct TransformResult {
   pub asset: Asset,
   pub dependencies: Vec<Dependency>,
+  /// Warnings and other non-fatal diagnostics found while transforming the asset
+  ///
+  /// Errors are not listed here, as they fail the transformation instead.
+  pub diagnostics: Vec<Diagnostic>,
+  /// The CSS of CSS-in-JS tagged templates, along with the locations of the templates
+  pub extracted_css: Vec<(String, SourceLocation)>,
+  /// Keys passed to the configured translation functions, along with their locations
+  pub i18n_keys: Vec<(String, SourceLocation)>,
+  /// Stable identifiers of the helpers that were inlined into the asset, such as
+  /// `_async_to_generator`
+  ///
+  /// Assets that list the same id inline identical code, so duplicates can be hoisted later.
+  pub injected_helper_ids: Vec<String>,
   /// The transformer signals through this field that its result should be invalidated
   /// if these paths change.
   pub invalidate_on_file_change: Vec<PathBuf>,
 }
 
+/// An exported symbol of an asset, as listed in an export report
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ExportEntry {
+  /// The name the symbol is exported as
+  pub name: String,
+  /// The name of the symbol within the transformed code
+  pub local: String,
+  pub loc: Option<SourceLocation>,
+}
+
+/// A transformed asset together with its dependencies, such as for building an asset graph
+#[derive(Debug, PartialEq, Serialize)]
+pub struct AssetWithDependencies {
+  pub asset: Asset,
+  pub dependencies: Vec<Dependency>,
+}
+
+impl TransformResult {
+  /// Keep only the asset and its dependencies, dropping the diagnostics and other metadata
+  pub fn into_asset_with_deps(self) -> AssetWithDependencies {
+    AssetWithDependencies {
+      asset: self.asset,
+      dependencies: self.dependencies,
+    }
+  }
+
+  /// List the symbols that are exported by the asset, such as for documentation tools
+  ///
+  /// The `*` symbols that stand in for unknown CommonJS exports and namespaces are left out.
+  pub fn export_report(&self) -> Vec<ExportEntry> {
+    self
+      .asset
+      .symbols
+      .iter()
+      .filter(|symbol| symbol.exported != "*")
+      .map(|symbol| ExportEntry {
+        name: symbol.exported.clone(),
+        local: symbol.local.clone(),
+        loc: symbol.loc.clone(),
+      })
+      .collect()
+  }
+
+  /// List the specifiers of dependencies that reference files by path or URL, such as `./img.png`
+  ///
+  /// Bare package specifiers, such as `lodash`, and specifiers without an extension are left out.
+  pub fn referenced_urls(&self) -> Vec<String> {
+    self
+      .dependencies
+      .iter()
+      .map(|dependency| dependency.specifier.as_str())
+      .filter(|specifier| {
+        let is_path_or_url =
+          specifier.starts_with('.') || specifier.starts_with('/') || specifier.contains("://");
+        let path = specifier.split(['?', '#']).next().unwrap_or_default();
+
+        is_path_or_url && Path::new(path).extension().is_some()
+      })
+      .map(String::from)
+      .collect()
+  }
+
+  /// Whether the transformed code differs from the `original` code
+  ///
+  /// Trailing line breaks are ignored, since transformers commonly end their output with one.
+  pub fn code_changed(&self, original: &Code) -> bool {
+    let trim = |code: &Code| {
+      let bytes = code.bytes();
+      let end = bytes
+        .iter()
+        .rposition(|byte| *byte != b'\n' && *byte != b'\r')
+        .map_or(0, |index| index + 1);
+
+      bytes[..end].to_vec()
+    };
+
+    trim(&self.asset.code) != trim(original)
+  }
+
+  /// Check the invariants that transformers are expected to uphold, such as for fuzzing
+  ///
+  /// All of the violations are listed, rather than only the first one that is found.
+  pub fn validate(&self) -> Result<(), Vec<String>> {
+    let mut violations = Vec::new();
+    let code = self.asset.code.to_string();
+
+    if self.asset.unique_key.is_none() {
+      violations.push(format!(
+        "The asset {} has no unique key",
+        self.asset.file_path.display()
+      ));
+    }
+
+    for dependency in &self.dependencies {
+      if let Some(placeholder) = &dependency.placeholder {
+        if !code.contains(placeholder.as_str()) {
+          violations.push(format!(
+            "The placeholder '{}' of the dependency on '{}' is not in the code",
+            placeholder, dependency.specifier
+          ));
+        }
+      }
+
+      for symbol in &dependency.symbols {
+        if !is_valid_local(&symbol.local) {
+          violations.push(format!(
+            "The symbol '{}' of the dependency on '{}' has an invalid local name '{}'",
+            symbol.exported, dependency.specifier, symbol.local
+          ));
+        }
+      }
+    }
+
+    let mut exported = HashSet::new();
+    for symbol in &self.asset.symbols {
+      if symbol.exported != "*" && !exported.insert(symbol.exported.as_str()) {
+        violations.push(format!(
+          "The symbol '{}' is exported more than once",
+          symbol.exported
+        ));
+      }
+    }
+
+    if violations.is_empty() {
+      Ok(())
+    } else {
+      Err(violations)
+    }
+  }
+}
+
+/// Whether a local name is `*` or a JavaScript identifier, such as `$abc$import$foo`
+fn is_valid_local(local: &str) -> bool {
+  let mut chars = local.chars();
+
+  match chars.next() {
+    Some('*') => local.len() == 1,
+    Some(first) if first.is_alphabetic() || first == '_' || first == '$' => {
+      chars.all(|char| char.is_alphanumeric() || char == '_' || char == '$')
+    }
+    _ => false,
+  }
+}
+
 /// Compile a single asset, discover dependencies, or convert the asset to a different format
 ///
 /// Many transformers are wrappers around other tools such as compilers and preprocessors, and are
@@ -145,3 +577,285 @@ pub trait TransformerPlugin: Debug + Send + Sync {
     input: TransformationInput,
   ) -> Result<TransformResult, anyhow::Error>;
 }
+
+#[cfg(test)]
+mod test {
+  use std::time::Instant;
+
+  use parcel_filesystem::in_memory_file_system::InMemoryFileSystem;
+  use serde::Deserialize;
+
+  use crate::types::Symbol;
+
+  use super::*;
+
+  #[derive(Debug, Deserialize, PartialEq)]
+  struct TsConfig {
+    strict: bool,
+  }
+
+  #[test]
+  fn test_configs_are_loaded_from_the_config_file_system() {
+    let project_root = PathBuf::from("/project-root");
+    let file_path = project_root.join("src").join("index.ts");
+    let config_path = project_root.join("tsconfig.json");
+
+    let file_system = Arc::new(InMemoryFileSystem::default());
+    file_system.write_file(&file_path, String::from("export const a = 1;"));
+    file_system.write_file(&config_path, String::from(r#"{ "strict": false }"#));
+
+    let config_file_system = Arc::new(InMemoryFileSystem::default());
+    config_file_system.write_file(&config_path, String::from(r#"{ "strict": true }"#));
+
+    let context = RunTransformContext::new(
+      file_system,
+      Arc::new(ParcelOptions::default()),
+      project_root.clone(),
+    )
+    .with_config_file_system(config_file_system);
+
+    let input = TransformationInput::InitialAsset(InitialAsset {
+      file_path: file_path.clone(),
+      ..InitialAsset::default()
+    });
+
+    assert_eq!(
+      input.read_code(context.file_system()).unwrap().to_string(),
+      "export const a = 1;"
+    );
+    assert!(!context.config_file_system().is_file(&file_path));
+
+    let config = context
+      .config_loader(file_path.parent().unwrap().to_path_buf())
+      .load_json_config::<TsConfig>("tsconfig.json")
+      .unwrap();
+
+    assert_eq!(config.path, config_path);
+    assert_eq!(config.contents, TsConfig { strict: true });
+  }
+
+  #[test]
+  fn test_inline_script_unique_key_is_relative_to_the_project_root() {
+    let make_script = |parent_path: &str, index: usize| {
+      InlineScript::new(
+        PathBuf::from(parent_path),
+        index,
+        String::default(),
+        Arc::new(Environment::default()),
+        SourceType::Module,
+      )
+    };
+
+    let key = make_script("/a/project/index.html", 0).unique_key(Path::new("/a/project"));
+
+    assert_eq!(
+      key,
+      make_script("/b/project/index.html", 0).unique_key(Path::new("/b/project"))
+    );
+    assert_ne!(
+      key,
+      make_script("/a/project/index.html", 1).unique_key(Path::new("/a/project"))
+    );
+    assert_ne!(
+      key,
+      make_script("/a/project/other.html", 0).unique_key(Path::new("/a/project"))
+    );
+  }
+
+  #[test]
+  fn test_referenced_urls() {
+    let result = TransformResult {
+      asset: Asset::default(),
+      dependencies: [
+        "./img.png",
+        "lodash",
+        "./utils",
+        "https://example.com/font.woff2",
+      ]
+      .into_iter()
+      .map(|specifier| Dependency {
+        specifier: String::from(specifier),
+        ..Dependency::default()
+      })
+      .collect(),
+      ..TransformResult::default()
+    };
+
+    assert_eq!(
+      result.referenced_urls(),
+      vec![
+        String::from("./img.png"),
+        String::from("https://example.com/font.woff2")
+      ]
+    );
+  }
+
+  fn make_valid_result() -> TransformResult {
+    TransformResult {
+      asset: Asset {
+        code: Arc::new(Code::from(String::from(
+          "var _dep = require(\"2f8e2b3c\");\nexports.a = _dep.b;",
+        ))),
+        file_path: PathBuf::from("index.js"),
+        symbols: vec![Symbol {
+          exported: String::from("a"),
+          local: String::from("$index$export$a"),
+          ..Symbol::default()
+        }],
+        unique_key: Some(String::from("index")),
+        ..Asset::default()
+      },
+      dependencies: vec![Dependency {
+        placeholder: Some(String::from("2f8e2b3c")),
+        specifier: String::from("./dep"),
+        symbols: vec![Symbol {
+          exported: String::from("b"),
+          local: String::from("$index$import$b"),
+          ..Symbol::default()
+        }],
+        ..Dependency::default()
+      }],
+      ..TransformResult::default()
+    }
+  }
+
+  #[test]
+  fn test_validate_valid_result() {
+    assert_eq!(make_valid_result().validate(), Ok(()));
+  }
+
+  #[test]
+  fn test_validate_reports_all_violations() {
+    let mut result = make_valid_result();
+    result.asset.code = Arc::new(Code::from(String::from("exports.a = 1;")));
+    result.asset.unique_key = None;
+    result.asset.symbols.push(result.asset.symbols[0].clone());
+    result.dependencies[0].symbols[0].local = String::from("not valid");
+
+    assert_eq!(
+      result.validate(),
+      Err(vec![
+        String::from("The asset index.js has no unique key"),
+        String::from("The placeholder '2f8e2b3c' of the dependency on './dep' is not in the code"),
+        String::from(
+          "The symbol 'b' of the dependency on './dep' has an invalid local name 'not valid'"
+        ),
+        String::from("The symbol 'a' is exported more than once"),
+      ])
+    );
+  }
+
+  #[derive(Debug)]
+  struct SlowTransformer {
+    duration: Duration,
+  }
+
+  impl TransformerPlugin for SlowTransformer {
+    fn transform(
+      &mut self,
+      context: &mut RunTransformContext,
+      input: TransformationInput,
+    ) -> Result<TransformResult, anyhow::Error> {
+      let duration = self.duration;
+      context.run_with_timeout(input.file_path(), move || thread::sleep(duration))?;
+
+      Ok(TransformResult {
+        asset: Asset {
+          file_path: input.file_path().to_path_buf(),
+          ..Asset::default()
+        },
+        ..TransformResult::default()
+      })
+    }
+  }
+
+  fn slow_transform(
+    duration: Duration,
+    timeout: Option<Duration>,
+  ) -> anyhow::Result<TransformResult> {
+    slow_transform_with_context(duration, timeout, RunTransformContext::default())
+  }
+
+  fn slow_transform_with_context(
+    duration: Duration,
+    timeout: Option<Duration>,
+    mut context: RunTransformContext,
+  ) -> anyhow::Result<TransformResult> {
+    if let Some(timeout) = timeout {
+      context = context.with_timeout(timeout);
+    }
+
+    let input = TransformationInput::InitialAsset(InitialAsset {
+      file_path: PathBuf::from("/project-root/slow.js"),
+      ..InitialAsset::default()
+    });
+
+    SlowTransformer { duration }.transform(&mut context, input)
+  }
+
+  #[test]
+  fn test_transform_that_exceeds_the_timeout_fails() {
+    let diagnostic = slow_transform(Duration::from_secs(1), Some(Duration::from_millis(1)))
+      .unwrap_err()
+      .downcast::<Diagnostic>()
+      .unwrap();
+
+    assert_eq!(
+      diagnostic.message,
+      "Transforming /project-root/slow.js took longer than the timeout of 1ms."
+    );
+    assert_eq!(
+      diagnostic.code_frames[0].path,
+      Some(PathBuf::from("/project-root/slow.js"))
+    );
+  }
+
+  #[test]
+  fn test_transform_semaphore_is_held_until_timed_out_work_completes() {
+    let semaphore = Arc::new(Semaphore::new(1));
+    let context = RunTransformContext::default().with_transform_semaphore(semaphore.clone());
+    let started = Instant::now();
+
+    let result = slow_transform_with_context(
+      Duration::from_millis(200),
+      Some(Duration::from_millis(1)),
+      context,
+    );
+    assert!(result.is_err());
+
+    // Blocks until the worker thread has finished and released its permit
+    let _permit = semaphore.acquire();
+    assert!(started.elapsed() >= Duration::from_millis(200));
+  }
+
+  #[test]
+  fn test_transform_within_the_timeout_succeeds() {
+    assert!(slow_transform(Duration::ZERO, Some(Duration::from_secs(10))).is_ok());
+    assert!(slow_transform(Duration::from_millis(1), None).is_ok());
+  }
+
+  #[test]
+  fn test_config_file_system_defaults_to_the_main_file_system() {
+    let project_root = PathBuf::from("/project-root");
+    let config_path = project_root.join("tsconfig.json");
+
+    let file_system = Arc::new(InMemoryFileSystem::default());
+    file_system.write_file(&config_path, String::from(r#"{ "strict": true }"#));
+
+    let context = RunTransformContext::new(
+      file_system,
+      Arc::new(ParcelOptions::default()),
+      project_root.clone(),
+    );
+
+    assert!(context.config_file_system().is_file(&config_path));
+    assert_eq!(
+      context
+        .config_loader(project_root.join("src"))
+        .load_json_config::<TsConfig>("tsconfig.json")
+        .unwrap()
+        .contents,
+      TsConfig { strict: true }
+    );
+  }
+}
@@ -0,0 +1,136 @@
+use std::sync::{Arc, Condvar, Mutex};
+
+/// A counting semaphore that blocks the current thread until a permit is available
+///
+/// This is used to bound how many expensive operations, such as SWC transforms, run at the same
+/// time across a thread pool.
+#[derive(Debug)]
+pub struct Semaphore {
+  available: Mutex<usize>,
+  released: Condvar,
+}
+
+impl Semaphore {
+  pub fn new(permits: usize) -> Self {
+    Self {
+      available: Mutex::new(permits),
+      released: Condvar::new(),
+    }
+  }
+
+  /// Wait for a permit to become available
+  ///
+  /// The permit is held until the returned guard is dropped.
+  pub fn acquire(&self) -> SemaphorePermit<'_> {
+    self.wait();
+
+    SemaphorePermit { semaphore: self }
+  }
+
+  /// Wait for a permit to become available, which can be moved to another thread
+  pub fn acquire_owned(self: Arc<Self>) -> OwnedSemaphorePermit {
+    self.wait();
+
+    OwnedSemaphorePermit { semaphore: self }
+  }
+
+  fn wait(&self) {
+    let mut available = self.available.lock().unwrap();
+    while *available == 0 {
+      available = self.released.wait(available).unwrap();
+    }
+
+    *available -= 1;
+  }
+
+  fn release(&self) {
+    *self.available.lock().unwrap() += 1;
+    self.released.notify_one();
+  }
+}
+
+/// A permit acquired from a [`Semaphore`], which is released on drop
+#[derive(Debug)]
+pub struct SemaphorePermit<'a> {
+  semaphore: &'a Semaphore,
+}
+
+impl Drop for SemaphorePermit<'_> {
+  fn drop(&mut self) {
+    self.semaphore.release();
+  }
+}
+
+/// A permit that owns a reference to its [`Semaphore`], which is released on drop
+#[derive(Debug)]
+pub struct OwnedSemaphorePermit {
+  semaphore: Arc<Semaphore>,
+}
+
+impl Drop for OwnedSemaphorePermit {
+  fn drop(&mut self) {
+    self.semaphore.release();
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::sync::atomic::{AtomicUsize, Ordering};
+  use std::sync::Arc;
+  use std::thread;
+  use std::time::Duration;
+
+  use super::*;
+
+  #[test]
+  fn test_single_permit_serializes_execution() {
+    let semaphore = Arc::new(Semaphore::new(1));
+    let active = Arc::new(AtomicUsize::new(0));
+    let max_active = Arc::new(AtomicUsize::new(0));
+
+    let handles = (0..4)
+      .map(|_| {
+        let semaphore = semaphore.clone();
+        let active = active.clone();
+        let max_active = max_active.clone();
+
+        thread::spawn(move || {
+          let _permit = semaphore.acquire();
+          let current = active.fetch_add(1, Ordering::SeqCst) + 1;
+          max_active.fetch_max(current, Ordering::SeqCst);
+          thread::sleep(Duration::from_millis(10));
+          active.fetch_sub(1, Ordering::SeqCst);
+        })
+      })
+      .collect::<Vec<_>>();
+
+    for handle in handles {
+      handle.join().unwrap();
+    }
+
+    assert_eq!(max_active.load(Ordering::SeqCst), 1);
+  }
+
+  #[test]
+  fn test_permits_are_released_on_drop() {
+    let semaphore = Semaphore::new(2);
+
+    let first = semaphore.acquire();
+    let second = semaphore.acquire();
+    drop(first);
+    drop(second);
+
+    assert_eq!(*semaphore.available.lock().unwrap(), 2);
+  }
+
+  #[test]
+  fn test_owned_permits_are_released_on_drop() {
+    let semaphore = Arc::new(Semaphore::new(1));
+
+    let permit = semaphore.clone().acquire_owned();
+    assert_eq!(*semaphore.available.lock().unwrap(), 0);
+
+    thread::spawn(move || drop(permit)).join().unwrap();
+    assert_eq!(*semaphore.available.lock().unwrap(), 1);
+  }
+}
@@ -72,6 +72,9 @@ pub struct Asset {
   /// Plugin specific metadata for the asset
   pub meta: JSONObject,
 
+  /// The source map of the code as JSON, when source maps are enabled for the environment
+  pub map: Option<String>,
+
   /// The pipeline defined in .parcelrc that the asset should be processed with
   pub pipeline: Option<String>,
 
@@ -144,8 +147,13 @@ pub struct Asset {
 
 impl Asset {
   pub fn id(&self) -> u64 {
-    let mut hasher = crate::hash::IdentifierHasher::default();
+    self.id_with_hasher(crate::hash::IdentifierHasher::default())
+  }
 
+  /// Compute the id of the asset with a custom hasher
+  ///
+  /// This hashes the same fields as `id()`, so that ids stay consistent for a given hasher.
+  pub fn id_with_hasher<H: Hasher>(&self, mut hasher: H) -> u64 {
     self.asset_type.hash(&mut hasher);
     self.env.hash(&mut hasher);
     self.file_path.hash(&mut hasher);
@@ -156,6 +164,18 @@ impl Asset {
     hasher.finish()
   }
 
+  /// Returns a copy of the asset at a different file path
+  ///
+  /// As the id of an asset is derived from its path, the copy has a different `id()` that matches
+  /// the id of any other asset created at the new path. The unique key is left as-is, since it is
+  /// assigned by the transformer rather than derived from the path.
+  pub fn with_file_path(&self, file_path: impl Into<PathBuf>) -> Asset {
+    Asset {
+      file_path: file_path.into(),
+      ..self.clone()
+    }
+  }
+
   pub fn set_interpreter(&mut self, shebang: impl Into<serde_json::Value>) {
     self.meta.insert("interpreter".into(), shebang.into());
   }
@@ -198,3 +218,53 @@ pub struct AssetStats {
   pub size: u32,
   pub time: u32,
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_id_with_hasher() {
+    let asset = Asset {
+      code: Arc::new(Code::from(String::from("function hello() {}"))),
+      file_path: "mock_path".into(),
+      ..Asset::default()
+    };
+
+    assert_eq!(
+      asset.id_with_hasher(crate::hash::IdentifierHasher::default()),
+      5787511958692361102
+    );
+    assert_eq!(asset.id(), 5787511958692361102);
+
+    let seeded_id = asset.id_with_hasher(xxhash_rust::xxh3::Xxh3::with_seed(42));
+
+    assert_ne!(seeded_id, asset.id());
+    assert_eq!(
+      seeded_id,
+      asset.id_with_hasher(xxhash_rust::xxh3::Xxh3::with_seed(42))
+    );
+  }
+
+  #[test]
+  fn test_with_file_path_updates_the_id() {
+    let asset = Asset {
+      code: Arc::new(Code::from(String::from("export const a = 1;"))),
+      file_path: PathBuf::from("/project/a.js"),
+      ..Asset::default()
+    };
+    let moved = asset.with_file_path("/project/b.js");
+
+    assert_ne!(moved.id(), asset.id());
+    assert_eq!(moved.file_path, PathBuf::from("/project/b.js"));
+    assert_eq!(
+      moved.id(),
+      Asset {
+        code: Arc::new(Code::from(String::from("export const a = 1;"))),
+        file_path: PathBuf::from("/project/b.js"),
+        ..Asset::default()
+      }
+      .id()
+    );
+  }
+}
@@ -1,4 +1,5 @@
 use core::panic;
+use std::collections::BTreeMap;
 use std::hash::Hash;
 use std::hash::Hasher;
 use std::path::PathBuf;
@@ -13,6 +14,7 @@ use crate::types::ExportsCondition;
 
 use super::bundle::BundleBehavior;
 use super::environment::Environment;
+use super::file_type::FileType;
 use super::json::JSONObject;
 use super::source::SourceLocation;
 use super::symbol::Symbol;
@@ -28,9 +30,20 @@ pub struct Dependency {
   ///
   pub bundle_behavior: BundleBehavior,
 
+  /// The name of the bundle the resolved asset is placed in, if requested by the importer
+  ///
+  /// This is set from `webpackChunkName` magic comments on dynamic imports.
+  #[serde(default)]
+  pub chunk_name: Option<String>,
+
   /// The environment of the dependency
   pub env: Arc<Environment>,
 
+  /// Import attributes of the dependency, such as `type: 'json'` from
+  /// `import('./data.json', { with: { type: 'json' } })`
+  #[serde(default)]
+  pub import_attributes: BTreeMap<String, String>,
+
   /// The location within the source file where the dependency was found
   #[serde(default)]
   pub loc: Option<SourceLocation>,
@@ -50,6 +63,13 @@ pub struct Dependency {
   #[serde(default)]
   pub pipeline: Option<String>,
 
+  /// Whether the resolved bundle can be preloaded, which packagers may add a hint for
+  ///
+  /// This is set on dynamic imports whose result is not awaited immediately, when the
+  /// `preload_unawaited_imports` option is enabled.
+  #[serde(default)]
+  pub preload: bool,
+
   /// Determines when the dependency should be loaded
   pub priority: Priority,
 
@@ -65,6 +85,13 @@ pub struct Dependency {
   /// The id of the asset with this dependency
   pub source_asset_id: Option<String>,
 
+  /// The type of the asset with this dependency
+  ///
+  /// Resolvers can use this to prefer extensions matching the importer, such as resolving `.ts`
+  /// files before `.js` files from TypeScript.
+  #[serde(default)]
+  pub source_asset_type: Option<FileType>,
+
   /// The file path of the asset with this dependency
   pub source_path: Option<PathBuf>,
 
@@ -109,9 +136,34 @@ pub struct Dependency {
   /// import expression.
   pub is_esm: bool,
 
+  /// Whether the dependency imports a CSS module, such as `./styles.module.css`
+  ///
+  /// The class names of CSS modules are scoped, so the pipeline should produce a map from the
+  /// original names to the scoped ones for the importing asset.
+  #[serde(default)]
+  pub is_css_module: bool,
+
   /// Whether the symbols vector of this dependency has had symbols added to it.
   pub has_symbols: bool,
 
+  /// Whether the dependency is a static import that imports no symbols, such as `import 'polyfill'`
+  ///
+  /// These are imported for their side effects only, so they are never removed by tree shaking.
+  #[serde(default)]
+  pub side_effect_only: bool,
+
+  /// Whether the dependency is only resolved to a URL, as with `import.meta.resolve('x')`
+  ///
+  /// The resolved asset is not imported by the dependency, so none of its symbols are used.
+  #[serde(default)]
+  pub resolve_only: bool,
+
+  /// Whether the specifier is a glob for a set of modules, such as `./locales/*.js`
+  ///
+  /// These come from dynamic imports of template literals, such as ``import(`./locales/${lang}.js`)``.
+  #[serde(default)]
+  pub is_context: bool,
+
   pub placeholder: Option<String>,
 }
 
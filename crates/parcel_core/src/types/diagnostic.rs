@@ -6,10 +6,10 @@ use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use std::{
   fmt::{Display, Formatter},
-  path::PathBuf,
+  path::{Path, PathBuf},
 };
 
-use super::File;
+use super::{File, PathStyle};
 
 /// Represents the kind of diagnostic
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
@@ -19,10 +19,23 @@ pub enum ErrorKind {
   Unknown,
 }
 
+/// Represents how severe a diagnostic is
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+  /// Fails the build
+  #[default]
+  Error,
+  /// Reported to the user, but does not fail the build
+  Warning,
+  /// Informational only, no action is required from the user
+  Info,
+}
+
 /// This is a user facing error for Parcel.
 ///
 /// Usually but not always this is linked to a source-code location.
-#[derive(Builder, Debug, Deserialize, PartialEq, Serialize)]
+#[derive(Builder, Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[builder(derive(Debug))]
 #[serde(rename_all = "camelCase")]
 pub struct Diagnostic {
@@ -51,6 +64,30 @@ pub struct Diagnostic {
   /// Consumers can also enable backtraces for more detailed origin information.
   #[builder(default)]
   pub origin: Option<String>,
+
+  /// Whether this diagnostic fails the build or is only reported to the user
+  #[builder(default)]
+  #[serde(default)]
+  pub severity: DiagnosticSeverity,
+
+  /// Fixes that editors can offer to apply for the user
+  #[builder(default)]
+  #[serde(default)]
+  pub suggestions: Vec<CodeSuggestion>,
+}
+
+impl Diagnostic {
+  /// Format the paths of the code frames in the given style
+  pub fn with_path_style(mut self, path_style: PathStyle, project_root: &Path) -> Self {
+    for code_frame in self.code_frames.iter_mut() {
+      code_frame.path = code_frame
+        .path
+        .take()
+        .map(|path| path_style.format_path(&path, project_root));
+    }
+
+    self
+  }
 }
 
 impl Display for Diagnostic {
@@ -120,6 +157,13 @@ impl From<PathBuf> for CodeFrame {
   }
 }
 
+/// A suggested fix, which replaces the code at `loc` with `replacement`
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CodeSuggestion {
+  pub loc: SourceLocation,
+  pub replacement: String,
+}
+
 /// Represents a snippet of code to highlight
 #[derive(Serialize, Default, Deserialize, Debug, PartialEq, Clone)]
 pub struct CodeHighlight {
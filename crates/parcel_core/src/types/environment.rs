@@ -68,11 +68,55 @@ pub struct Environment {
   pub source_map: Option<TargetSourceMapOptions>,
 
   pub source_type: SourceType,
+
+  /// The name of the target this environment was created for, if any
+  ///
+  /// This is used to keep assets that are shared between multiple targets apart, for example
+  /// when generating their unique keys.
+  ///
+  pub target_name: Option<String>,
+}
+
+impl Environment {
+  /// An environment for ES modules that run in modern browsers
+  pub fn browser_esm() -> Self {
+    Self::with_context(EnvironmentContext::Browser, OutputFormat::EsModule)
+  }
+
+  /// An environment for CommonJS modules that run in Node
+  pub fn node_cjs() -> Self {
+    Self::with_context(EnvironmentContext::Node, OutputFormat::CommonJS)
+  }
+
+  /// An environment for classic web workers, which are loaded as scripts
+  pub fn web_worker() -> Self {
+    Self::with_context(EnvironmentContext::WebWorker, OutputFormat::Global)
+  }
+
+  fn with_context(context: EnvironmentContext, output_format: OutputFormat) -> Self {
+    Self {
+      context,
+      include_node_modules: IncludeNodeModules::from(context),
+      output_format,
+      source_type: SourceType::Module,
+      ..Self::default()
+    }
+  }
+
+  /// A stable string that identifies this environment, for use in cache keys
+  ///
+  /// This is derived from the same fields as the `Hash` implementation, so environments that are
+  /// equal always produce the same key.
+  pub fn cache_key(&self) -> String {
+    let mut hasher = crate::hash::IdentifierHasher::default();
+    self.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+  }
 }
 
 impl Hash for Environment {
   fn hash<H: Hasher>(&self, state: &mut H) {
-    // Hashing intentionally does not include loc
+    // Hashing intentionally does not include loc or target_name
     self.context.hash(state);
     self.engines.hash(state);
     self.include_node_modules.hash(state);
@@ -87,7 +131,7 @@ impl Hash for Environment {
 
 impl PartialEq for Environment {
   fn eq(&self, other: &Self) -> bool {
-    // Equality intentionally does not include loc
+    // Equality intentionally does not include loc or target_name
     self.context == other.context
       && self.engines == other.engines
       && self.include_node_modules == other.include_node_modules
@@ -210,3 +254,78 @@ pub struct TargetSourceMapOptions {
   ///
   source_root: Option<String>,
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_cache_key_is_equal_for_equal_environments() {
+    let env_1 = Environment::node_cjs();
+    let env_2 = Environment {
+      target_name: Some(String::from("other")),
+      ..env_1.clone()
+    };
+
+    assert_eq!(env_1, env_2);
+    assert_eq!(env_1.cache_key(), env_2.cache_key());
+    assert_eq!(env_1.cache_key(), env_1.clone().cache_key());
+  }
+
+  #[test]
+  fn test_cache_key_differs_for_different_environments() {
+    let env = Environment::default();
+    let different_envs = [
+      Environment {
+        context: EnvironmentContext::Node,
+        ..env.clone()
+      },
+      Environment {
+        output_format: OutputFormat::EsModule,
+        ..env.clone()
+      },
+      Environment {
+        source_type: SourceType::Script,
+        ..env.clone()
+      },
+      Environment {
+        should_optimize: true,
+        ..env.clone()
+      },
+    ];
+
+    for different_env in different_envs {
+      assert_ne!(env.cache_key(), different_env.cache_key());
+    }
+  }
+
+  #[test]
+  fn test_browser_esm() {
+    let env = Environment::browser_esm();
+
+    assert_eq!(env.context, EnvironmentContext::Browser);
+    assert_eq!(env.include_node_modules, IncludeNodeModules::Bool(true));
+    assert_eq!(env.output_format, OutputFormat::EsModule);
+    assert_eq!(env.source_type, SourceType::Module);
+  }
+
+  #[test]
+  fn test_node_cjs() {
+    let env = Environment::node_cjs();
+
+    assert_eq!(env.context, EnvironmentContext::Node);
+    assert_eq!(env.include_node_modules, IncludeNodeModules::Bool(false));
+    assert_eq!(env.output_format, OutputFormat::CommonJS);
+    assert_eq!(env.source_type, SourceType::Module);
+  }
+
+  #[test]
+  fn test_web_worker() {
+    let env = Environment::web_worker();
+
+    assert_eq!(env.context, EnvironmentContext::WebWorker);
+    assert_eq!(env.include_node_modules, IncludeNodeModules::Bool(true));
+    assert_eq!(env.output_format, OutputFormat::Global);
+    assert_eq!(env.source_type, SourceType::Module);
+  }
+}
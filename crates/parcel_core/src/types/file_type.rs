@@ -1,11 +1,17 @@
 use std::hash::Hash;
 
+use json_comments::CommentSettings;
 use serde::Deserialize;
 use serde::Serialize;
 
+use super::SourceType;
+
 /// Represents a file type by its extension
 ///
 /// Defaults to `FileType::Js` for convenience.
+///
+/// New variants are added at the end, as the derived `Hash` depends on the order of the variants
+/// and asset ids must not change.
 #[derive(Default, Debug, Clone, PartialEq, Hash)]
 pub enum FileType {
   Css,
@@ -17,6 +23,10 @@ pub enum FileType {
   Ts,
   Tsx,
   Other(String),
+  Cjs,
+  Mjs,
+  Json5,
+  Jsonc,
 }
 
 impl Serialize for FileType {
@@ -41,8 +51,12 @@ impl<'de> Deserialize<'de> for FileType {
 impl FileType {
   pub fn extension(&self) -> &str {
     match self {
+      FileType::Cjs => "cjs",
       FileType::Js => "js",
+      FileType::Mjs => "mjs",
       FileType::Json => "json",
+      FileType::Json5 => "json5",
+      FileType::Jsonc => "jsonc",
       FileType::Jsx => "jsx",
       FileType::Ts => "ts",
       FileType::Tsx => "tsx",
@@ -55,15 +69,198 @@ impl FileType {
   pub fn from_extension(ext: &str) -> Self {
     match ext {
       "js" => FileType::Js,
-      "mjs" => FileType::Js,
-      "cjs" => FileType::Js,
+      "mjs" => FileType::Mjs,
+      "cjs" => FileType::Cjs,
       "jsx" => FileType::Jsx,
       "ts" => FileType::Ts,
       "tsx" => FileType::Tsx,
       "json" => FileType::Json,
+      "json5" => FileType::Json5,
+      "jsonc" => FileType::Jsonc,
       "css" => FileType::Css,
       "html" => FileType::Html,
       ext => FileType::Other(ext.to_string()),
     }
   }
+
+  /// Whether files of this type are always ES modules or always scripts
+  ///
+  /// This is `None` when the extension alone does not tell, as for `.js` files, in which case the
+  /// source type of the environment applies.
+  pub fn source_type_hint(&self) -> Option<SourceType> {
+    match self {
+      FileType::Mjs | FileType::Ts | FileType::Tsx => Some(SourceType::Module),
+      FileType::Cjs => Some(SourceType::Script),
+      _ => None,
+    }
+  }
+
+  /// Whether files of this type contain JSON data, in any of the supported dialects
+  pub fn is_json(&self) -> bool {
+    matches!(self, FileType::Json | FileType::Json5 | FileType::Jsonc)
+  }
+
+  /// Whether files of this type are parsed leniently, allowing comments and trailing commas
+  ///
+  /// These are only accepted by the JSON5 and JSONC dialects. See [`FileType::parse_json`].
+  pub fn is_lenient_json(&self) -> bool {
+    matches!(self, FileType::Json5 | FileType::Jsonc)
+  }
+
+  /// Parse the source of a JSON file of this type
+  ///
+  /// The `//` and `/* */` comments and trailing commas of the lenient dialects are stripped, so
+  /// that the value can be emitted as strict JSON. Both are an error in `.json` files.
+  pub fn parse_json(&self, source: &str) -> serde_json::Result<serde_json::Value> {
+    if self.is_lenient_json() {
+      let mut source = source.to_string();
+      #[allow(deprecated)]
+      json_comments::strip_comments_in_place(&mut source, CommentSettings::c_style(), true)
+        .map_err(serde_json::Error::io)?;
+      serde_json::from_str(&source)
+    } else {
+      serde_json::from_str(source)
+    }
+  }
+
+  /// Emit a JSON file of this type as a CommonJS module that exports its value
+  ///
+  /// The value is emitted as strict JSON and parsed at runtime, as `JSON.parse` is faster than
+  /// evaluating the equivalent object literal.
+  pub fn json_module(&self, source: &str) -> serde_json::Result<String> {
+    let json = self.parse_json(source)?.to_string();
+
+    Ok(format!(
+      "module.exports = JSON.parse({});",
+      serde_json::to_string(&json)?
+    ))
+  }
+}
+
+/// The extensions to try, in order, for specifiers without an extension imported from `importer`
+///
+/// TypeScript importers prefer TypeScript files, so that `./x` resolves to `./x.ts` even when a
+/// compiled `./x.js` sits next to it. Other importers prefer JavaScript files.
+pub fn resolution_extensions_for(importer: &FileType) -> &'static [&'static str] {
+  match importer {
+    FileType::Ts => &["ts", "tsx", "js", "jsx", "mjs", "cjs", "json"],
+    FileType::Tsx => &["tsx", "ts", "jsx", "js", "mjs", "cjs", "json"],
+    _ => &["js", "jsx", "mjs", "cjs", "json", "ts", "tsx"],
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_from_extension() {
+    assert_eq!(FileType::from_extension("mjs"), FileType::Mjs);
+    assert_eq!(FileType::from_extension("cjs"), FileType::Cjs);
+    assert_eq!(FileType::Mjs.extension(), "mjs");
+    assert_eq!(FileType::Cjs.extension(), "cjs");
+  }
+
+  #[test]
+  fn test_json_dialects() {
+    for (extension, file_type) in [
+      ("json", FileType::Json),
+      ("json5", FileType::Json5),
+      ("jsonc", FileType::Jsonc),
+    ] {
+      assert_eq!(FileType::from_extension(extension), file_type);
+      assert_eq!(file_type.extension(), extension);
+      assert!(file_type.is_json());
+    }
+
+    assert!(!FileType::Json.is_lenient_json());
+    assert!(FileType::Json5.is_lenient_json());
+    assert!(FileType::Jsonc.is_lenient_json());
+    assert!(!FileType::Js.is_json());
+  }
+
+  #[test]
+  fn test_lenient_json_strips_comments() {
+    let source = r#"{
+  // The port to listen on
+  "port": 1234, /* "host": "localhost" */
+  "url": "http://localhost"
+}"#;
+
+    let value = FileType::Jsonc.parse_json(source).unwrap();
+    assert_eq!(
+      value.to_string(),
+      r#"{"port":1234,"url":"http://localhost"}"#
+    );
+    assert_eq!(FileType::Json5.parse_json(source).unwrap(), value);
+  }
+
+  #[test]
+  fn test_lenient_json_strips_trailing_commas() {
+    let source = "{\n  \"hosts\": [\"a\", \"b\",],\n  \"port\": 1234, // The port\n}";
+
+    assert_eq!(
+      FileType::Jsonc.parse_json(source).unwrap().to_string(),
+      r#"{"hosts":["a","b"],"port":1234}"#
+    );
+    assert!(FileType::Json.parse_json(r#"{ "port": 1234, }"#).is_err());
+  }
+
+  #[test]
+  fn test_json_module_exports_the_cleaned_value() {
+    let source = "{\n  // The port to listen on\n  \"port\": 1234,\n}";
+
+    assert_eq!(
+      FileType::Jsonc.json_module(source).unwrap(),
+      r#"module.exports = JSON.parse("{\"port\":1234}");"#
+    );
+    assert!(FileType::Json.json_module(source).is_err());
+  }
+
+  #[test]
+  fn test_comments_in_strict_json_are_an_error() {
+    assert!(FileType::Json
+      .parse_json("{\n  // The port\n  \"port\": 1234\n}")
+      .is_err());
+    assert!(FileType::Json.parse_json(r#"{ "port": 1234 }"#).is_ok());
+  }
+
+  #[test]
+  fn test_resolution_extensions_for_typescript_importers() {
+    assert_eq!(
+      resolution_extensions_for(&FileType::Ts),
+      ["ts", "tsx", "js", "jsx", "mjs", "cjs", "json"]
+    );
+    assert_eq!(
+      resolution_extensions_for(&FileType::Tsx),
+      ["tsx", "ts", "jsx", "js", "mjs", "cjs", "json"]
+    );
+  }
+
+  #[test]
+  fn test_resolution_extensions_for_javascript_importers() {
+    for importer in [FileType::Js, FileType::Jsx, FileType::Mjs, FileType::Cjs] {
+      assert_eq!(
+        resolution_extensions_for(&importer),
+        ["js", "jsx", "mjs", "cjs", "json", "ts", "tsx"]
+      );
+    }
+  }
+
+  #[test]
+  fn test_source_type_hint() {
+    assert_eq!(FileType::Mjs.source_type_hint(), Some(SourceType::Module));
+    assert_eq!(FileType::Ts.source_type_hint(), Some(SourceType::Module));
+    assert_eq!(FileType::Tsx.source_type_hint(), Some(SourceType::Module));
+    assert_eq!(FileType::Cjs.source_type_hint(), Some(SourceType::Script));
+    assert_eq!(FileType::Js.source_type_hint(), None);
+    assert_eq!(FileType::Jsx.source_type_hint(), None);
+    assert_eq!(FileType::Json.source_type_hint(), None);
+    assert_eq!(FileType::Css.source_type_hint(), None);
+    assert_eq!(FileType::Html.source_type_hint(), None);
+    assert_eq!(
+      FileType::Other(String::from("foo")).source_type_hint(),
+      None
+    );
+  }
 }
@@ -1,39 +1,309 @@
 use std::collections::HashMap;
 use std::fmt::Display;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serialize;
 
 use super::engines::Engines;
+use super::FileType;
 use super::OutputFormat;
 
 /// The options passed into Parcel either through the CLI or the programmatic API
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ParcelOptions {
+  /// Parse `.ts` files as TSX, for codebases that use JSX in them
+  ///
+  /// This prevents the use of `<Type>value` type assertions in `.ts` files, as in `.tsx` files.
+  #[serde(default)]
+  pub allow_jsx_in_ts: bool,
+
+  /// Annotate top-level calls that create exported values with `/*#__PURE__*/`
+  ///
+  /// This lets minifiers drop these calls when the export is unused. Existing annotations are
+  /// preserved.
+  #[serde(default)]
+  pub annotate_pure: bool,
+
+  /// Treat all JavaScript files as ES modules, skipping CommonJS interop analysis
+  ///
+  /// This overrides the source type of the environment, and `require` calls within these files
+  /// are reported as warnings. Files with a `.cjs` extension are not affected.
+  #[serde(default)]
+  pub assume_esm: bool,
+
+  /// Compile JSX using the automatic runtime, which imports its helpers from the JSX import source
+  ///
+  /// In development builds, this uses `jsxDEV` calls that include the source location of each
+  /// element for better dev tooling.
+  #[serde(default)]
+  pub automatic_jsx_runtime: bool,
+
+  /// Warn about each `var` declaration in ES modules, for projects that only use `let` and `const`
+  #[serde(default)]
+  pub ban_var: bool,
+
+  /// A banner, such as a license comment or a prelude, that is prepended to transformed code
+  pub banner: Option<String>,
+
   pub config: Option<String>,
 
+  /// Collect dynamic imports of relative template literals as context dependencies
+  ///
+  /// A context dependency stands for the set of modules the import may load, with a glob as its
+  /// specifier, so that ``import(`./locales/${lang}.js`)`` depends on `./locales/*.js`.
+  #[serde(default)]
+  pub context_dynamic_imports: bool,
+
   /// Path to the parcel core node_module. This will be used to resolve built-ins or runtime files.
   ///
   /// In the future this may be replaced with embedding those files into the rust binary.
   pub core_path: PathBuf,
 
+  /// How files with an unknown extension are treated by transformers
+  #[serde(default)]
+  pub default_file_type: DefaultFileType,
+
   #[serde(default)]
   pub default_target_options: DefaultTargetOptions,
 
+  /// Parse and apply legacy decorators, such as `@dec class {}`
+  ///
+  /// Decorators are compiled the same way as TypeScript's `experimentalDecorators`.
+  #[serde(default)]
+  pub decorators: bool,
+
+  /// Sort the symbols of assets and dependencies by their exported and local names
+  ///
+  /// By default symbols are listed in the order the transformer found them, which may change
+  /// between versions and cause churn in snapshots.
+  #[serde(default)]
+  pub deterministic_symbol_order: bool,
+
+  /// How the paths of source files are shown in diagnostics
+  #[serde(default)]
+  pub diagnostic_path_style: PathStyle,
+
+  /// Start the code of each asset with a `/* module: <unique key> */` comment, for debugging
+  ///
+  /// Assets without a unique key are labelled with their id instead.
+  #[serde(default)]
+  pub emit_module_id_comment: bool,
+
   pub entries: Vec<String>,
   pub env: Option<HashMap<String, String>>,
 
+  /// Collect the CSS of CSS-in-JS tagged templates, such as `styled.div\`...\``, without
+  /// transforming it
+  ///
+  /// The CSS is listed in the transform result, so that tooling can process it separately.
+  #[serde(default)]
+  pub extract_css_in_js: bool,
+
   #[serde(rename = "defaultConfig")]
   pub fallback_config: Option<String>,
 
+  /// Bundle the files that `fetch` calls with a relative string literal URL point to
+  ///
+  /// This is off by default, as fetched URLs often refer to resources that are served separately.
+  #[serde(default)]
+  pub follow_fetch_urls: bool,
+
+  /// Replace `typeof window` with the value it has in the context of the environment
+  ///
+  /// This lets feature checks such as `typeof window !== 'undefined'` be folded, so the code for
+  /// other environments is removed. It is off by default, as some code defines `window` itself,
+  /// for example when testing browser code in Node.
+  #[serde(default)]
+  pub fold_environment_checks: bool,
+
+  /// Names of translation functions, such as `t` or `i18n.t`, whose keys are collected
+  ///
+  /// The string literal arguments of calls to these functions are listed in the transform result,
+  /// so that they can be picked up by extraction tooling.
+  #[serde(default)]
+  pub i18n_functions: Vec<String>,
+
+  /// How default imports of CommonJS modules are resolved when ES modules are converted to CommonJS
+  ///
+  /// Scope hoisted output is not affected, as interop is handled by the packager there.
+  #[serde(default)]
+  pub interop: InteropStrategy,
+
+  /// Preserve the names of functions and classes when minifying
+  ///
+  /// This is useful when the names are relied on at runtime, for example in error stack traces.
+  #[serde(default)]
+  pub keep_names: bool,
+
   #[serde(default)]
   pub log_level: LogLevel,
 
+  /// The number of SWC helpers that can be inlined into a single script
+  ///
+  /// Scripts that need more helpers than this import them from `@swc/helpers` instead, so the
+  /// helpers are not duplicated across large files.
+  #[serde(default)]
+  pub max_inline_helpers: Option<usize>,
+
+  /// The size in bytes above which source maps are dropped, with a warning
+  ///
+  /// Source maps of very large or generated files can be many times larger than their code, so
+  /// this keeps them out of the output.
+  #[serde(default)]
+  pub max_source_map_bytes: Option<usize>,
+
+  /// Minify the output of the JS transformer, mangling local names
+  ///
+  /// This is separate from the `should_optimize` of each environment, which leaves minification
+  /// to the optimizers. Top level names are kept, as they are referenced by other assets.
+  #[serde(default)]
+  pub minify: bool,
+
   #[serde(default)]
   pub mode: BuildMode,
+
+  /// The character used to join the parts of composite ids, such as the unique keys of assets
+  ///
+  /// Unique keys combine the target name and the asset id, and default to `:` as a delimiter.
+  /// Setting this to a character such as `~`, `-` or `.` keeps them safe to use in file names.
+  #[serde(default)]
+  pub name_delimiter: Option<char>,
+
+  /// Obfuscate the local names of exports, except for the exports in this list
+  ///
+  /// This is meant for libraries that do not want their internal helpers to be named in the
+  /// output. The exported names are kept, so the listed exports are the ones that stay readable.
+  #[serde(default)]
+  pub obfuscate_exports: Option<Vec<String>>,
+
+  /// The number of spaces to indent non-minified output with
+  ///
+  /// When this is 0, the default indentation of the transformer is used.
+  #[serde(default)]
+  pub output_indent: usize,
+
+  /// Mark dynamic imports that are not awaited immediately, such as `const p = import('./x')`,
+  /// to be preloaded
+  #[serde(default)]
+  pub preload_unawaited_imports: bool,
+
+  /// Leave JSX as-is in the output, rather than compiling it to function calls
+  ///
+  /// This is useful when downstream tooling handles JSX. Dependencies are still collected.
+  #[serde(default)]
+  pub preserve_jsx: bool,
+
+  /// Pass file paths relative to the project root to transformers, and store the source path of
+  /// dependencies relative to it, rather than absolute paths
+  ///
+  /// Relative paths keep the output reproducible and caches portable across machines, while
+  /// absolute paths are easier to debug with. Dependencies are still resolved from the absolute
+  /// path.
+  #[serde(default)]
+  pub relative_file_paths: bool,
+
+  /// Store the source path of dependencies relative to the project root, without changing the
+  /// file paths passed to transformers
+  ///
+  /// This keeps caches portable and asset graphs readable. Dependencies are still resolved from
+  /// the absolute path.
+  #[serde(default)]
+  pub relative_source_paths: bool,
+
+  /// Report imports that are never referenced within a module as info diagnostics
+  #[serde(default)]
+  pub report_unused_imports: bool,
+
+  /// Warn about relative imports without a file extension in ES modules, such as `import './x'`
+  ///
+  /// These are resolved by Parcel, but fail in Node and browsers that follow the ESM rules.
+  #[serde(default)]
+  pub require_explicit_extensions: bool,
+
+  /// Global names that are never renamed, such as `jQuery`, for globals that the host page expects
+  ///
+  /// Top level declarations with these names are kept as-is by scope hoisting and minification.
+  #[serde(default)]
+  pub reserved_globals: Vec<String>,
+
+  /// Embed the original source code in source maps, so that they are self-contained
+  #[serde(default)]
+  pub source_map_include_content: bool,
+
+  /// Fail the build on dynamic imports that cannot be statically resolved, such as `import(x)`
+  ///
+  /// These are otherwise reported as warnings.
+  #[serde(default)]
+  pub strict_dynamic_imports: bool,
+
+  /// Lower the output to this ECMAScript version, rather than inferring it from the engines
+  ///
+  /// This gives predictable output regardless of the browserslist configuration.
+  #[serde(default)]
+  pub target_es_version: Option<EsVersion>,
+
+  /// Whether the transformed code ends with a line break
+  ///
+  /// SWC always ends its output with a line break, which this can strip for exact comparisons.
+  #[serde(default)]
+  pub trailing_newline: TrailingNewline,
+
+  /// Warn about assets with more dependencies than this, which often points to barrel files that
+  /// slow down builds
+  #[serde(default)]
+  pub warn_dependency_count: Option<usize>,
+
+  /// Warn about relative imports that go up more directories than this, such as `../../../../x`,
+  /// which often points to a missing alias
+  #[serde(default)]
+  pub warn_relative_depth: Option<usize>,
+}
+
+impl ParcelOptions {
+  /// Create options from the environment variables of the process
+  ///
+  /// All variables are made available through `env`, while `mode` is read from `NODE_ENV` and
+  /// `log_level` from `PARCEL_LOG_LEVEL`.
+  pub fn from_env() -> Self {
+    Self::from_env_vars(std::env::vars())
+  }
+
+  /// Create a copy of the options with `maps` merged into `env`
+  ///
+  /// Maps are merged in order on top of the existing `env`, so variables in later maps take
+  /// precedence. This is useful to layer env files, such as `.env`, `.env.production` and
+  /// `.env.local`.
+  pub fn with_merged_env(&self, maps: &[HashMap<String, String>]) -> Self {
+    let mut env = self.env.clone().unwrap_or_default();
+    for map in maps {
+      env.extend(map.iter().map(|(key, value)| (key.clone(), value.clone())));
+    }
+
+    Self {
+      env: Some(env),
+      ..self.clone()
+    }
+  }
+
+  fn from_env_vars(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+    let env: HashMap<String, String> = vars.into_iter().collect();
+
+    Self {
+      log_level: env
+        .get("PARCEL_LOG_LEVEL")
+        .map(|level| LogLevel::from(level.as_str()))
+        .unwrap_or_default(),
+      mode: env
+        .get("NODE_ENV")
+        .map(|mode| BuildMode::from(mode.as_str()))
+        .unwrap_or_default(),
+      env: Some(env),
+      ..Self::default()
+    }
+  }
 }
 
 #[derive(Clone, Debug, Default, Hash, PartialEq, Serialize)]
@@ -55,6 +325,16 @@ impl Display for BuildMode {
   }
 }
 
+impl From<&str> for BuildMode {
+  fn from(mode: &str) -> Self {
+    match mode {
+      "development" => BuildMode::Development,
+      "production" => BuildMode::Production,
+      _ => BuildMode::Other(mode.to_string()),
+    }
+  }
+}
+
 impl<'de> Deserialize<'de> for BuildMode {
   fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
   where
@@ -62,11 +342,25 @@ impl<'de> Deserialize<'de> for BuildMode {
   {
     let s = String::deserialize(deserializer)?;
 
-    Ok(match s.as_str() {
-      "development" => BuildMode::Development,
-      "production" => BuildMode::Production,
-      _ => BuildMode::Other(s),
-    })
+    Ok(BuildMode::from(s.as_str()))
+  }
+}
+
+/// The treatment of files with an extension that does not map to a known `FileType`
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultFileType {
+  /// Fail the transformation with a diagnostic
+  Error,
+
+  /// Transform the file as if it had the given type
+  #[serde(untagged)]
+  FileType(FileType),
+}
+
+impl Default for DefaultFileType {
+  fn default() -> Self {
+    DefaultFileType::FileType(FileType::Js)
   }
 }
 
@@ -108,3 +402,194 @@ pub enum LogLevel {
   Verbose,
   Warn,
 }
+
+impl From<&str> for LogLevel {
+  /// Unknown levels fall back to the default level
+  fn from(level: &str) -> Self {
+    match level.to_lowercase().as_str() {
+      "info" => LogLevel::Info,
+      "none" => LogLevel::None,
+      "verbose" => LogLevel::Verbose,
+      "warn" => LogLevel::Warn,
+      _ => LogLevel::Error,
+    }
+  }
+}
+
+/// An ECMAScript version that output can be lowered to
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EsVersion {
+  Es5,
+  Es2015,
+  Es2016,
+  Es2017,
+  Es2018,
+  Es2019,
+  Es2020,
+  Es2021,
+  Es2022,
+  EsNext,
+}
+
+/// How the default import of a CommonJS module is resolved
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InteropStrategy {
+  /// Use `exports.default` for modules with an `__esModule` flag, and `module.exports` otherwise
+  #[default]
+  Babel,
+  /// Always use `module.exports`, as Node does
+  Node,
+  /// Always use `exports.default`
+  None,
+}
+
+/// How file paths are shown to the user
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathStyle {
+  /// Absolute paths, which editors and terminals can open directly
+  #[default]
+  Absolute,
+  /// Paths relative to the project root, which are shorter and the same across machines
+  Relative,
+}
+
+impl PathStyle {
+  /// Format a path in this style, leaving paths outside of the project root absolute
+  pub fn format_path(&self, path: &Path, project_root: &Path) -> PathBuf {
+    match self {
+      PathStyle::Absolute => path.to_path_buf(),
+      PathStyle::Relative => path
+        .strip_prefix(project_root)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| path.to_path_buf()),
+    }
+  }
+}
+
+/// How the line break at the end of transformed code is handled
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrailingNewline {
+  /// Keep the code as it was output
+  #[default]
+  Preserve,
+  /// End the code with exactly one line break
+  Ensure,
+  /// Remove all line breaks from the end of the code
+  Strip,
+}
+
+impl TrailingNewline {
+  /// Apply the policy to the end of `code`
+  pub fn apply(&self, code: &str) -> String {
+    match self {
+      TrailingNewline::Preserve => code.to_string(),
+      TrailingNewline::Ensure => format!("{}\n", code.trim_end_matches(['\n', '\r'])),
+      TrailingNewline::Strip => code.trim_end_matches(['\n', '\r']).to_string(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars
+      .iter()
+      .map(|(key, value)| (key.to_string(), value.to_string()))
+      .collect()
+  }
+
+  #[test]
+  fn test_from_env_vars() {
+    let options = ParcelOptions::from_env_vars(vars(&[
+      ("NODE_ENV", "production"),
+      ("PARCEL_LOG_LEVEL", "verbose"),
+      ("API_URL", "https://example.com"),
+    ]));
+
+    assert_eq!(options.mode, BuildMode::Production);
+    assert_eq!(options.log_level, LogLevel::Verbose);
+    assert_eq!(
+      options.env.unwrap().get("API_URL"),
+      Some(&String::from("https://example.com"))
+    );
+  }
+
+  #[test]
+  fn test_from_env_vars_defaults() {
+    let options = ParcelOptions::from_env_vars(vars(&[("PARCEL_LOG_LEVEL", "unknown")]));
+
+    assert_eq!(options.mode, BuildMode::Development);
+    assert_eq!(options.log_level, LogLevel::Error);
+    assert_eq!(
+      ParcelOptions::from_env_vars(vars(&[("NODE_ENV", "test")])).mode,
+      BuildMode::Other(String::from("test"))
+    );
+  }
+
+  #[test]
+  fn test_with_merged_env() {
+    let options = ParcelOptions {
+      env: Some(HashMap::from_iter(vars(&[
+        ("BASE", "options"),
+        ("MODE", "options"),
+      ]))),
+      ..ParcelOptions::default()
+    };
+
+    let merged = options.with_merged_env(&[
+      HashMap::from_iter(vars(&[("MODE", "base"), ("API_URL", "base")])),
+      HashMap::from_iter(vars(&[("API_URL", "production")])),
+      HashMap::from_iter(vars(&[("API_URL", "local"), ("LOCAL", "local")])),
+    ]);
+
+    assert_eq!(
+      merged.env,
+      Some(HashMap::from_iter(vars(&[
+        ("API_URL", "local"),
+        ("BASE", "options"),
+        ("LOCAL", "local"),
+        ("MODE", "base"),
+      ])))
+    );
+    assert_eq!(
+      options.env,
+      Some(HashMap::from_iter(vars(&[
+        ("BASE", "options"),
+        ("MODE", "options")
+      ])))
+    );
+  }
+
+  #[test]
+  fn test_trailing_newline() {
+    for code in ["a", "a\n", "a\r\n\n"] {
+      assert_eq!(TrailingNewline::Ensure.apply(code), "a\n");
+      assert_eq!(TrailingNewline::Strip.apply(code), "a");
+      assert_eq!(TrailingNewline::Preserve.apply(code), code);
+    }
+  }
+
+  #[test]
+  fn test_path_style_keeps_paths_outside_the_project_root() {
+    let project_root = Path::new("/project");
+
+    assert_eq!(
+      PathStyle::Relative.format_path(Path::new("/project/src/a.js"), project_root),
+      PathBuf::from("src/a.js")
+    );
+    assert_eq!(
+      PathStyle::Relative.format_path(Path::new("/other/a.js"), project_root),
+      PathBuf::from("/other/a.js")
+    );
+    assert_eq!(
+      PathStyle::Absolute.format_path(Path::new("/project/src/a.js"), project_root),
+      PathBuf::from("/project/src/a.js")
+    );
+  }
+}
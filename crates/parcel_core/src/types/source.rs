@@ -26,4 +26,73 @@ pub struct SourceLocation {
   pub end: Location,
 }
 
+impl SourceLocation {
+  /// Whether the position at `line` and `column` falls within this location
+  ///
+  /// The start position is inclusive, while the end position is exclusive.
+  ///
+  pub fn contains(&self, line: usize, column: usize) -> bool {
+    let position = (line, column);
+
+    (self.start.line, self.start.column) <= position && position < (self.end.line, self.end.column)
+  }
+}
+
 pub struct SourceMap {}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn source_location(start: (usize, usize), end: (usize, usize)) -> SourceLocation {
+    SourceLocation {
+      file_path: PathBuf::from("index.js"),
+      start: Location {
+        line: start.0,
+        column: start.1,
+      },
+      end: Location {
+        line: end.0,
+        column: end.1,
+      },
+    }
+  }
+
+  #[test]
+  fn test_contains_positions_inside() {
+    let loc = source_location((1, 5), (1, 10));
+
+    assert!(loc.contains(1, 6));
+    assert!(loc.contains(1, 9));
+  }
+
+  #[test]
+  fn test_contains_positions_on_the_boundary() {
+    let loc = source_location((1, 5), (1, 10));
+
+    assert!(loc.contains(1, 5));
+    assert!(!loc.contains(1, 10));
+  }
+
+  #[test]
+  fn test_contains_positions_outside() {
+    let loc = source_location((2, 5), (2, 10));
+
+    assert!(!loc.contains(2, 4));
+    assert!(!loc.contains(2, 11));
+    assert!(!loc.contains(1, 7));
+    assert!(!loc.contains(3, 7));
+  }
+
+  #[test]
+  fn test_contains_multi_line_positions() {
+    let loc = source_location((2, 10), (4, 3));
+
+    assert!(loc.contains(2, 40));
+    assert!(loc.contains(3, 1));
+    assert!(loc.contains(3, 100));
+    assert!(loc.contains(4, 2));
+    assert!(!loc.contains(2, 9));
+    assert!(!loc.contains(4, 3));
+  }
+}
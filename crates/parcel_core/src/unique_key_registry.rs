@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::diagnostic_error;
+use crate::plugin::TransformResult;
+use crate::types::{CodeFrame, DiagnosticBuilder, DiagnosticError};
+
+/// Keeps track of the unique keys of transformed assets across a build
+///
+/// Unique keys are used to look up assets in maps downstream, so two assets with the same key
+/// would overwrite each other. This reports such collisions when the second asset is registered.
+///
+#[derive(Debug, Default)]
+pub struct UniqueKeyRegistry {
+  assets_by_key: Mutex<HashMap<String, (u64, PathBuf)>>,
+}
+
+impl UniqueKeyRegistry {
+  /// Record the unique key of the transformed asset, if it has one
+  ///
+  /// This errors when a different asset has already registered the same key. Registering the same
+  /// asset again is allowed, as happens when it is transformed again after a change.
+  pub fn register(&self, result: &TransformResult) -> Result<(), DiagnosticError> {
+    let Some(unique_key) = &result.asset.unique_key else {
+      return Ok(());
+    };
+
+    let asset_id = result.asset.id();
+    let mut assets_by_key = self.assets_by_key.lock().unwrap();
+    let (existing_id, existing_path) = assets_by_key
+      .entry(unique_key.clone())
+      .or_insert_with(|| (asset_id, result.asset.file_path.clone()));
+
+    if *existing_id == asset_id {
+      return Ok(());
+    }
+
+    Err(diagnostic_error!(DiagnosticBuilder::default()
+      .code_frames(vec![
+        CodeFrame::from(existing_path.clone()),
+        CodeFrame::from(result.asset.file_path.clone()),
+      ])
+      .hints(vec![String::from(
+        "Unique keys must be distinct, so derive them from more than the contents of the asset."
+      )])
+      .message(format!(
+        "The unique key '{}' of {} is already used by {}.",
+        unique_key,
+        result.asset.file_path.display(),
+        existing_path.display()
+      ))))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::types::Asset;
+
+  use super::*;
+
+  #[test]
+  fn test_duplicate_unique_keys_are_reported() {
+    let registry = UniqueKeyRegistry::default();
+    let a = TransformResult {
+      asset: Asset {
+        file_path: PathBuf::from("/a.js"),
+        unique_key: Some(String::from("key")),
+        ..Asset::default()
+      },
+      ..TransformResult::default()
+    };
+    let b = TransformResult {
+      asset: Asset {
+        file_path: PathBuf::from("/b.js"),
+        unique_key: Some(String::from("key")),
+        ..Asset::default()
+      },
+      ..TransformResult::default()
+    };
+
+    assert!(registry.register(&a).is_ok());
+    assert_eq!(
+      registry.register(&b).map_err(|error| error.to_string()),
+      Err(String::from(
+        "The unique key 'key' of /b.js is already used by /a.js."
+      ))
+    );
+  }
+
+  #[test]
+  fn test_distinct_and_repeated_registrations_are_allowed() {
+    let registry = UniqueKeyRegistry::default();
+    let a = TransformResult {
+      asset: Asset {
+        file_path: PathBuf::from("/a.js"),
+        unique_key: Some(String::from("a")),
+        ..Asset::default()
+      },
+      ..TransformResult::default()
+    };
+    let b = TransformResult {
+      asset: Asset {
+        file_path: PathBuf::from("/b.js"),
+        unique_key: Some(String::from("b")),
+        ..Asset::default()
+      },
+      ..TransformResult::default()
+    };
+
+    assert!(registry.register(&a).is_ok());
+    assert!(registry.register(&b).is_ok());
+    assert!(registry.register(&a).is_ok());
+    assert!(registry.register(&TransformResult::default()).is_ok());
+  }
+}
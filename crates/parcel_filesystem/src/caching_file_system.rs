@@ -0,0 +1,161 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use dashmap::DashMap;
+
+use crate::FileSystem;
+use crate::FileSystemRealPathCache;
+use crate::FileSystemRef;
+
+type StatCache = DashMap<PathBuf, bool, xxhash_rust::xxh3::Xxh3Builder>;
+
+/// Wraps a `FileSystem` and memoizes the results of `is_file` and `is_dir` calls
+///
+/// Resolution stats the same paths many times over, so this avoids hitting the underlying
+/// file-system for paths that have already been checked. Cached results are kept until the path
+/// is invalidated, which should happen whenever the file-system reports a change to it.
+pub struct CachingFileSystem {
+  inner: FileSystemRef,
+  is_dir_cache: StatCache,
+  is_file_cache: StatCache,
+}
+
+impl CachingFileSystem {
+  pub fn new(inner: FileSystemRef) -> Self {
+    Self {
+      inner,
+      is_dir_cache: StatCache::default(),
+      is_file_cache: StatCache::default(),
+    }
+  }
+
+  /// Remove the cached results for a path, so that the next call will stat it again
+  pub fn invalidate(&self, path: &Path) {
+    self.is_dir_cache.remove(path);
+    self.is_file_cache.remove(path);
+  }
+
+  /// Remove all cached results
+  pub fn clear(&self) {
+    self.is_dir_cache.clear();
+    self.is_file_cache.clear();
+  }
+}
+
+/// Look up a cached stat result, or stat the path and cache it
+///
+/// The stat runs without holding a lock on the cache, so that slow IO does not block other
+/// threads that use the same shard of the map. Two threads that miss at the same time may both
+/// stat the path, and the last result to be inserted wins.
+fn cached_stat(cache: &StatCache, path: &Path, stat: impl FnOnce() -> bool) -> bool {
+  if let Some(result) = cache.get(path) {
+    return *result;
+  }
+
+  let result = stat();
+  cache.insert(path.to_path_buf(), result);
+  result
+}
+
+impl FileSystem for CachingFileSystem {
+  fn cwd(&self) -> std::io::Result<PathBuf> {
+    self.inner.cwd()
+  }
+
+  fn canonicalize_base(&self, path: &Path) -> std::io::Result<PathBuf> {
+    self.inner.canonicalize_base(path)
+  }
+
+  fn canonicalize(&self, path: &Path, cache: &FileSystemRealPathCache) -> std::io::Result<PathBuf> {
+    self.inner.canonicalize(path, cache)
+  }
+
+  fn create_directory(&self, path: &Path) -> std::io::Result<()> {
+    self.invalidate(path);
+    self.inner.create_directory(path)
+  }
+
+  fn read_to_string(&self, path: &Path) -> std::io::Result<String> {
+    self.inner.read_to_string(path)
+  }
+
+  fn is_file(&self, path: &Path) -> bool {
+    cached_stat(&self.is_file_cache, path, || self.inner.is_file(path))
+  }
+
+  fn is_dir(&self, path: &Path) -> bool {
+    cached_stat(&self.is_dir_cache, path, || self.inner.is_dir(path))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::sync::Arc;
+
+  use crate::MockFileSystem;
+
+  use super::*;
+
+  #[test]
+  fn test_repeated_calls_hit_the_cache() {
+    let mut inner = MockFileSystem::new();
+    inner.expect_is_file().times(1).return_const(true);
+    inner.expect_is_dir().times(1).return_const(false);
+
+    let file_system = CachingFileSystem::new(Arc::new(inner));
+    let path = Path::new("/index.js");
+
+    for _ in 0..3 {
+      assert!(file_system.is_file(path));
+      assert!(!file_system.is_dir(path));
+    }
+  }
+
+  #[test]
+  fn test_paths_are_cached_separately() {
+    let mut inner = MockFileSystem::new();
+    inner
+      .expect_is_file()
+      .times(2)
+      .returning(|path| path == Path::new("/index.js"));
+
+    let file_system = CachingFileSystem::new(Arc::new(inner));
+
+    assert!(file_system.is_file(Path::new("/index.js")));
+    assert!(!file_system.is_file(Path::new("/other.js")));
+    assert!(file_system.is_file(Path::new("/index.js")));
+    assert!(!file_system.is_file(Path::new("/other.js")));
+  }
+
+  #[test]
+  fn test_invalidate_forces_a_new_stat() {
+    let mut inner = MockFileSystem::new();
+    inner.expect_is_file().times(2).return_const(true);
+
+    let file_system = CachingFileSystem::new(Arc::new(inner));
+    let path = Path::new("/index.js");
+
+    assert!(file_system.is_file(path));
+    assert!(file_system.is_file(path));
+
+    file_system.invalidate(path);
+
+    assert!(file_system.is_file(path));
+    assert!(file_system.is_file(path));
+  }
+
+  #[test]
+  fn test_clear_forces_a_new_stat() {
+    let mut inner = MockFileSystem::new();
+    inner.expect_is_dir().times(2).return_const(true);
+
+    let file_system = CachingFileSystem::new(Arc::new(inner));
+    let path = Path::new("/src");
+
+    assert!(file_system.is_dir(path));
+
+    file_system.clear();
+
+    assert!(file_system.is_dir(path));
+  }
+}
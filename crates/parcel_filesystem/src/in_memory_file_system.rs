@@ -11,8 +11,12 @@ use crate::FileSystem;
 enum InMemoryFileSystemEntry {
   File { contents: String },
   Directory,
+  Symlink { target: PathBuf },
 }
 
+/// The maximum number of symlinks followed when resolving a path, matching the Linux limit
+const MAX_SYMLINK_HOPS: usize = 40;
+
 /// In memory implementation of the `FileSystem` trait, for testing purpouses.
 #[derive(Debug)]
 pub struct InMemoryFileSystem {
@@ -29,16 +33,78 @@ impl InMemoryFileSystem {
   }
 
   /// Write a file at path.
+  ///
+  /// Symlinks in the parent directories are followed, so that the file is written into their
+  /// targets, as on a real file-system.
   pub fn write_file(&self, path: &Path, contents: String) {
     let path = self.canonicalize_impl(path);
+    let path = match (path.parent(), path.file_name()) {
+      (Some(parent), Some(file_name)) => self
+        .resolve_symlinks(parent)
+        .map_or(path.clone(), |parent| parent.join(file_name)),
+      _ => path,
+    };
+
+    self.insert_entry(path, InMemoryFileSystemEntry::File { contents });
+  }
+
+  /// Create a symlink at `link` that points to `target`.
+  ///
+  /// Relative targets are resolved from the directory containing the link, as on a real
+  /// file-system. The target does not need to exist.
+  pub fn symlink(&self, target: &Path, link: &Path) {
+    let link = self.canonicalize_impl(link);
+    let target = match link.parent() {
+      Some(parent) if target.is_relative() => self.canonicalize_impl(&parent.join(target)),
+      _ => self.canonicalize_impl(target),
+    };
+
+    self.insert_entry(link, InMemoryFileSystemEntry::Symlink { target });
+  }
+
+  fn insert_entry(&self, path: PathBuf, entry: InMemoryFileSystemEntry) {
     let mut files = self.files.write().unwrap();
-    files.insert(path.clone(), InMemoryFileSystemEntry::File { contents });
 
     let mut dir = path.parent();
     while let Some(path) = dir {
-      files.insert(path.to_path_buf(), InMemoryFileSystemEntry::Directory);
+      files
+        .entry(path.to_path_buf())
+        .or_insert(InMemoryFileSystemEntry::Directory);
       dir = path.parent();
     }
+
+    files.insert(path, entry);
+  }
+
+  /// Normalize a path and follow any symlinks in it, erroring on symlink cycles
+  fn resolve_symlinks(&self, path: &Path) -> std::io::Result<PathBuf> {
+    let files = self.files.read().unwrap();
+    let mut path = self.canonicalize_impl(path);
+    let mut hops = 0;
+
+    'resolve: loop {
+      let mut resolved = PathBuf::new();
+      let mut components = path.components();
+
+      while let Some(component) = components.next() {
+        resolved.push(component);
+
+        if let Some(InMemoryFileSystemEntry::Symlink { target }) = files.get(&resolved) {
+          hops += 1;
+          if hops > MAX_SYMLINK_HOPS {
+            return Err(std::io::Error::other(format!(
+              "Too many levels of symbolic links: {}",
+              resolved.display()
+            )));
+          }
+
+          path = self.canonicalize_impl(&target.join(components.as_path()));
+          continue 'resolve;
+        }
+      }
+
+      return Ok(resolved);
+    }
   }
 
   fn canonicalize_impl(&self, path: &Path) -> PathBuf {
@@ -97,7 +163,7 @@ impl FileSystem for InMemoryFileSystem {
   }
 
   fn canonicalize_base(&self, path: &Path) -> std::io::Result<PathBuf> {
-    Ok(self.canonicalize_impl(path))
+    self.resolve_symlinks(path)
   }
 
   fn create_directory(&self, path: &Path) -> std::io::Result<()> {
@@ -108,7 +174,7 @@ impl FileSystem for InMemoryFileSystem {
   }
 
   fn read_to_string(&self, path: &Path) -> std::io::Result<String> {
-    let path = self.canonicalize_impl(path);
+    let path = self.resolve_symlinks(path)?;
     let files = self.files.read().unwrap();
     files.get(&path).map_or_else(
       || {
@@ -123,19 +189,24 @@ impl FileSystem for InMemoryFileSystem {
           std::io::ErrorKind::InvalidInput,
           "Path is a directory",
         )),
+        InMemoryFileSystemEntry::Symlink { .. } => unreachable!("Symlinks are already resolved"),
       },
     )
   }
 
   fn is_file(&self, path: &Path) -> bool {
-    let path = self.canonicalize_impl(path);
+    let Ok(path) = self.resolve_symlinks(path) else {
+      return false;
+    };
     let files = self.files.read().unwrap();
     let file = files.get(&path);
     matches!(file, Some(InMemoryFileSystemEntry::File { .. }))
   }
 
   fn is_dir(&self, path: &Path) -> bool {
-    let path = self.canonicalize_impl(path);
+    let Ok(path) = self.resolve_symlinks(path) else {
+      return false;
+    };
     let files = self.files.read().unwrap();
     let file = files.get(&path);
     matches!(file, Some(InMemoryFileSystemEntry::Directory { .. }))
@@ -236,6 +307,70 @@ mod test {
     assert!(fs.is_file(Path::new("/foo/bar")));
   }
 
+  #[test]
+  fn test_read_file_through_symlink() {
+    let fs = InMemoryFileSystem::default();
+    fs.write_file(
+      &root_dir().join("store/pkg/index.js"),
+      "contents".to_string(),
+    );
+    fs.symlink(
+      Path::new("../store/pkg"),
+      &root_dir().join("node_modules/pkg"),
+    );
+
+    let link = root_dir().join("node_modules/pkg/index.js");
+    assert_eq!(fs.read_to_string(&link).unwrap(), "contents");
+    assert!(fs.is_file(&link));
+    assert!(fs.is_dir(&root_dir().join("node_modules/pkg")));
+    assert_eq!(
+      fs.canonicalize(&link, &Default::default()).unwrap(),
+      root_dir().join("store/pkg/index.js")
+    );
+  }
+
+  #[test]
+  fn test_write_file_through_symlink() {
+    let fs = InMemoryFileSystem::default();
+    fs.create_directory(&root_dir().join("store/pkg")).unwrap();
+    fs.symlink(
+      Path::new("../store/pkg"),
+      &root_dir().join("node_modules/pkg"),
+    );
+
+    fs.write_file(
+      &root_dir().join("node_modules/pkg/index.js"),
+      "contents".to_string(),
+    );
+
+    assert_eq!(
+      fs.read_to_string(&root_dir().join("store/pkg/index.js"))
+        .unwrap(),
+      "contents"
+    );
+    assert_eq!(
+      fs.read_to_string(&root_dir().join("node_modules/pkg/index.js"))
+        .unwrap(),
+      "contents"
+    );
+  }
+
+  #[test]
+  fn test_symlink_cycle_is_an_error() {
+    let fs = InMemoryFileSystem::default();
+    fs.symlink(&root_dir().join("b"), &root_dir().join("a"));
+    fs.symlink(&root_dir().join("a"), &root_dir().join("b"));
+
+    let path = root_dir().join("a/index.js");
+    let error = fs.read_to_string(&path).unwrap_err();
+
+    assert!(error
+      .to_string()
+      .contains("Too many levels of symbolic links"));
+    assert!(!fs.is_file(&path));
+    assert!(fs.canonicalize(&path, &Default::default()).is_err());
+  }
+
   #[cfg(target_os = "windows")]
   mod windows_tests {
     use super::*;
@@ -4,6 +4,9 @@ use std::sync::Arc;
 
 use dashmap::DashMap;
 
+/// File-system wrapper that memoizes stat calls
+pub mod caching_file_system;
+
 /// In-memory file-system for testing
 pub mod in_memory_file_system;
 
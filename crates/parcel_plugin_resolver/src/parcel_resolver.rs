@@ -406,7 +406,7 @@ mod test {
   use parcel_core::{
     config_loader::ConfigLoader,
     plugin::PluginLogger,
-    types::{Dependency, Diagnostic, ErrorKind},
+    types::{Dependency, Diagnostic, DiagnosticSeverity, Environment, ErrorKind},
   };
   use parcel_filesystem::in_memory_file_system::InMemoryFileSystem;
   use std::path::PathBuf;
@@ -454,7 +454,9 @@ mod test {
         kind: ErrorKind::NotFound,
         hints: Vec::new(),
         message: String::from("Cannot find module 'foo.js'"),
-        origin: Some(String::from("parcel_plugin_resolver::parcel_resolver"))
+        origin: Some(String::from("parcel_plugin_resolver::parcel_resolver")),
+        severity: DiagnosticSeverity::Error,
+        suggestions: Vec::new(),
       }
     );
   }
@@ -487,7 +489,9 @@ mod test {
         hints: Vec::new(),
         kind: ErrorKind::Unknown,
         message: String::from("Module 'foo/bar' is not exported from the 'foo' package"),
-        origin: Some(String::from("parcel_plugin_resolver::parcel_resolver"))
+        origin: Some(String::from("parcel_plugin_resolver::parcel_resolver")),
+        severity: DiagnosticSeverity::Error,
+        suggestions: Vec::new(),
       }
     );
   }
@@ -545,4 +549,94 @@ mod test {
       })
     )
   }
+
+  fn resolve_with_package_json(
+    package_json: &str,
+    specifier: &str,
+    env: Environment,
+  ) -> anyhow::Result<Resolved> {
+    let fs = Arc::new(InMemoryFileSystem::default());
+    let package_dir = Path::new("/foo/node_modules/pkg");
+
+    fs.write_file(Path::new("/foo/index.js"), String::default());
+    fs.write_file(
+      &package_dir.join("package.json"),
+      String::from(package_json),
+    );
+    for file in ["main.js", "browser.js", "lib/node.js", "lib/browser.js"] {
+      fs.write_file(&package_dir.join(file), String::default());
+    }
+
+    let plugin_context = PluginContext {
+      config: Arc::new(ConfigLoader {
+        fs,
+        project_root: PathBuf::default(),
+        search_path: PathBuf::from("/foo"),
+      }),
+      logger: PluginLogger::default(),
+      options: Arc::new(PluginOptions::default()),
+    };
+
+    ParcelResolver::new(&plugin_context).resolve(ResolveContext {
+      dependency: Arc::new(Dependency {
+        env: Arc::new(env),
+        resolve_from: Some(PathBuf::from("/foo/index.js")),
+        specifier: specifier.into(),
+        ..Dependency::default()
+      }),
+      pipeline: None,
+      specifier: specifier.into(),
+    })
+  }
+
+  fn resolved_file_path(resolved: Resolved) -> PathBuf {
+    match resolved.resolution {
+      Resolution::Resolved(resolution) => resolution.file_path,
+      resolution => panic!("Expected a resolved file path, got {:?}", resolution),
+    }
+  }
+
+  /// A node environment that still bundles node_modules, so that packages are resolved
+  fn node_env() -> Environment {
+    Environment {
+      context: EnvironmentContext::Node,
+      ..Environment::default()
+    }
+  }
+
+  #[test]
+  fn browser_field_string_replaces_main() {
+    let package_json = r#"{ "name": "pkg", "main": "main.js", "browser": "browser.js" }"#;
+
+    let resolve = |env: Environment| {
+      resolved_file_path(resolve_with_package_json(package_json, "pkg", env).unwrap())
+    };
+
+    assert!(resolve(Environment::browser_esm()).ends_with("node_modules/pkg/browser.js"));
+    assert!(resolve(node_env()).ends_with("node_modules/pkg/main.js"));
+  }
+
+  #[test]
+  fn browser_field_map_remaps_and_stubs_modules() {
+    let package_json = r#"{
+      "name": "pkg",
+      "main": "main.js",
+      "browser": {
+        "./lib/node.js": "./lib/browser.js",
+        "./main.js": false
+      }
+    }"#;
+
+    let resolve = |specifier: &str, env: Environment| {
+      resolved_file_path(resolve_with_package_json(package_json, specifier, env).unwrap())
+    };
+
+    assert!(resolve("pkg/lib/node.js", Environment::browser_esm())
+      .ends_with("node_modules/pkg/lib/browser.js"));
+    assert!(resolve("pkg", Environment::browser_esm()).ends_with("_empty.js"));
+
+    // The browser field is only used when building for browsers
+    assert!(resolve("pkg/lib/node.js", node_env()).ends_with("node_modules/pkg/lib/node.js"));
+    assert!(resolve("pkg", node_env()).ends_with("node_modules/pkg/main.js"));
+  }
 }
@@ -1,5 +1,7 @@
 #![deny(unused_crate_dependencies)]
 
-pub use transformer::ParcelJsTransformerPlugin;
+pub use transformer::{
+  transform_and_list_specifiers, transform_stream, ParcelJsTransformerPlugin, TransformMeta,
+};
 
 mod transformer;
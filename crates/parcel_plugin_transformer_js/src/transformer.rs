@@ -1,13 +1,29 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use anyhow::{anyhow, Error};
+use swc_core::atoms::Atom;
 
+use parcel_core::diagnostic;
+use parcel_core::diagnostic_error;
 use parcel_core::plugin::TransformerPlugin;
 use parcel_core::plugin::{RunTransformContext, TransformResult, TransformationInput};
 use parcel_core::types::engines::EnvironmentFeature;
-use parcel_core::types::{Asset, BuildMode, FileType, LogLevel, OutputFormat, SourceType};
+use parcel_core::types::{
+  Asset, BuildMode, Code, CodeFrame, DefaultFileType, Dependency, Diagnostic, DiagnosticBuilder,
+  DiagnosticSeverity, Environment, EnvironmentContext, EsVersion, FileType, InteropStrategy,
+  LogLevel, OutputFormat, ParcelOptions, SourceType,
+};
 
 mod conversion;
 #[cfg(test)]
 mod test_helpers;
+mod validation;
+
+/// A directive that forces CommonJS output for a single asset, for rare interop cases
+const CJS_OUTPUT_DIRECTIVE: &str = "use cjs-output";
 
 /// This is a rust only `TransformerPlugin` implementation for JS assets that goes through the
 /// default SWC transformer.
@@ -27,6 +43,19 @@ impl ParcelJsTransformerPlugin {
   pub fn new() -> Self {
     Self {}
   }
+
+  /// The version of the SWC transformer, which changes with toolchain upgrades
+  pub fn transformer_version() -> &'static str {
+    parcel_js_swc_core::VERSION
+  }
+
+  /// A key for caching the results of transforming assets in `env`
+  ///
+  /// This includes the transformer version, so that cached results are not reused after
+  /// upgrading.
+  pub fn cache_key(&self, env: &Environment) -> String {
+    format!("{}:{}", Self::transformer_version(), env.cache_key())
+  }
 }
 
 impl TransformerPlugin for ParcelJsTransformerPlugin {
@@ -42,93 +71,419 @@ impl TransformerPlugin for ParcelJsTransformerPlugin {
     let is_node = env.context.is_node();
     let source_code = input.read_code(file_system)?;
 
-    let transformation_result = parcel_js_swc_core::transform(
-      parcel_js_swc_core::Config {
-        code: source_code.bytes().to_vec(),
-        // TODO Lift context up into constructor to improve performance?
-        env: context
-          .options()
-          .env
-          .clone()
-          .unwrap_or_default()
-          .iter()
-          .map(|(key, value)| (key.as_str().into(), value.as_str().into()))
-          .collect(),
-        filename: input
-          .file_path()
-          .to_str()
-          .ok_or_else(|| anyhow!("Invalid non UTF-8 file-path"))?
-          .to_string(),
-        insert_node_globals: !is_node && env.source_type != SourceType::Script,
-        is_browser: env.context.is_browser(),
-        is_development: context.options().mode == BuildMode::Development,
-        is_esm_output: env.output_format == OutputFormat::EsModule,
-        is_library: env.is_library,
-        is_worker: env.context.is_worker(),
-        node_replacer: is_node,
-        project_root: context.project_root().to_string_lossy().into_owned(),
-        replace_env: !is_node,
-        scope_hoist: env.should_scope_hoist && env.source_type != SourceType::Script,
-        source_maps: env.source_map.is_some(),
-        source_type: match env.source_type {
-          SourceType::Module => parcel_js_swc_core::SourceType::Module,
-          SourceType::Script => parcel_js_swc_core::SourceType::Script,
-        },
-        supports_module_workers: env.should_scope_hoist
-          && env.engines.supports(EnvironmentFeature::WorkerModule),
-        trace_bailouts: context.options().log_level == LogLevel::Verbose,
-        ..parcel_js_swc_core::Config::default()
-      },
-      None,
-    )?;
-
-    // TODO handle errors properly
-    if let Some(errors) = transformation_result.diagnostics {
-      return Err(anyhow!(format!("{:#?}", errors)));
-    }
-
     let file_path = input.file_path();
-    let asset_type = FileType::from_extension(
+    validation::validate_environment(&env, file_path)?;
+
+    let asset_type = match FileType::from_extension(
       file_path
         .extension()
         .and_then(|s| s.to_str())
         .unwrap_or_default(),
-    );
+    ) {
+      FileType::Other(extension) => match &context.options().default_file_type {
+        DefaultFileType::Error => {
+          return Err(diagnostic_error!(DiagnosticBuilder::default()
+            .code_frames(vec![CodeFrame::from(file_path.to_path_buf())])
+            .hints(vec![String::from(
+              "Configure a transformer for this extension, or set a default file type."
+            )])
+            .message(format!("Unknown file extension \"{}\".", extension))))
+        }
+        DefaultFileType::FileType(file_type) => file_type.clone(),
+      },
+      asset_type => asset_type,
+    };
+
+    // The extension takes precedence over the environment, so `.cjs` files are never assumed to
+    // be ES modules
+    let source_type = asset_type.source_type_hint().unwrap_or_else(|| {
+      if context.options().assume_esm {
+        SourceType::Module
+      } else {
+        env.source_type
+      }
+    });
+    let env = if source_type != env.source_type {
+      Arc::new(Environment {
+        source_type,
+        ..(*env).clone()
+      })
+    } else {
+      env
+    };
+
+    let has_cjs_output_directive = env.output_format != OutputFormat::CommonJS
+      && has_directive(
+        std::str::from_utf8(source_code.bytes()).unwrap_or_default(),
+        CJS_OUTPUT_DIRECTIVE,
+      );
+    let env = if has_cjs_output_directive {
+      Arc::new(Environment {
+        output_format: OutputFormat::CommonJS,
+        ..(*env).clone()
+      })
+    } else {
+      env
+    };
+
+    // Paths relative to the project root keep the output reproducible across machines
+    let transformer_file_path = if context.options().relative_file_paths {
+      file_path
+        .strip_prefix(context.project_root())
+        .unwrap_or(file_path)
+    } else {
+      file_path
+    };
+
+    // NODE_ENV always reflects the build mode, unless it is explicitly set in the options
+    let mut transformer_env: HashMap<Atom, Atom> = context
+      .options()
+      .env
+      .clone()
+      .unwrap_or_default()
+      .iter()
+      .map(|(key, value)| (key.as_str().into(), value.as_str().into()))
+      .collect();
+
+    transformer_env
+      .entry("NODE_ENV".into())
+      .or_insert_with(|| context.options().mode.to_string().into());
+
+    let transformer_config = parcel_js_swc_core::Config {
+      annotate_pure: context.options().annotate_pure,
+      automatic_jsx_runtime: context.options().automatic_jsx_runtime,
+      ban_var: context.options().ban_var,
+      code: source_code.bytes().to_vec(),
+      context_dynamic_imports: context.options().context_dynamic_imports,
+      decorators: context.options().decorators,
+      // TODO Lift context up into constructor to improve performance?
+      env: transformer_env,
+      extract_css_in_js: context.options().extract_css_in_js,
+      follow_fetch_urls: context.options().follow_fetch_urls,
+      // Worklets run in the browser, but do not have a window either
+      fold_environment_checks: context.options().fold_environment_checks
+        && env.context != EnvironmentContext::Worklet,
+      filename: transformer_file_path
+        .to_str()
+        .ok_or_else(|| anyhow!("Invalid non UTF-8 file-path"))?
+        .to_string(),
+      insert_node_globals: !is_node && env.source_type != SourceType::Script,
+      interop: convert_interop(context.options().interop),
+      is_browser: env.context.is_browser(),
+      is_development: context.options().mode == BuildMode::Development,
+      i18n_functions: context.options().i18n_functions.clone(),
+      is_esm_output: env.output_format == OutputFormat::EsModule,
+      is_jsx: matches!(asset_type, FileType::Jsx | FileType::Tsx)
+        || (asset_type == FileType::Ts && context.options().allow_jsx_in_ts),
+      is_library: env.is_library,
+      is_type_script: matches!(asset_type, FileType::Ts | FileType::Tsx),
+      is_worker: env.context.is_worker(),
+      keep_names: context.options().keep_names,
+      minify: context.options().minify,
+      node_replacer: is_node,
+      obfuscate_exports: context.options().obfuscate_exports.clone(),
+      output_indent: context.options().output_indent,
+      preserve_jsx: context.options().preserve_jsx,
+      project_root: context.project_root().to_string_lossy().into_owned(),
+      replace_env: !is_node,
+      reserved_globals: context.options().reserved_globals.clone(),
+      scope_hoist: env.should_scope_hoist && env.source_type != SourceType::Script,
+      source_map_include_content: context.options().source_map_include_content,
+      source_maps: env.source_map.is_some(),
+      source_type: match env.source_type {
+        SourceType::Module => parcel_js_swc_core::SourceType::Module,
+        SourceType::Script => parcel_js_swc_core::SourceType::Script,
+      },
+      strict_dynamic_imports: context.options().strict_dynamic_imports,
+      supports_module_workers: env.should_scope_hoist
+        && env.engines.supports(EnvironmentFeature::WorkerModule),
+      target_es_version: context.options().target_es_version.map(convert_es_version),
+      trace_bailouts: context.options().log_level == LogLevel::Verbose,
+      ..parcel_js_swc_core::Config::default()
+    };
+
+    let config = transformer_config.clone();
+    let mut transformation_result = context.run_with_timeout(file_path, move || {
+      parcel_js_swc_core::transform(config, None)
+    })??;
+
+    // Scripts that inline too many helpers are transformed again, importing them instead
+    let inlined_helper_count = transformation_result.injected_helpers.len();
+    let exceeds_max_inline_helpers = context
+      .options()
+      .max_inline_helpers
+      .is_some_and(|max_inline_helpers| inlined_helper_count > max_inline_helpers);
+
+    if exceeds_max_inline_helpers {
+      let config = parcel_js_swc_core::Config {
+        external_helpers: true,
+        ..transformer_config
+      };
+      transformation_result = context.run_with_timeout(file_path, move || {
+        parcel_js_swc_core::transform(config, None)
+      })??;
+    }
 
     let asset = Asset {
       asset_type,
       code: source_code,
       env: env.clone(),
       file_path: file_path.to_path_buf(),
+      unique_key: input.unique_key(context.project_root()),
       ..Asset::default()
     };
 
-    let config = parcel_js_swc_core::Config::default();
     let options = context.options();
-    let result = conversion::convert_result(asset, &config, transformation_result, &options)
-      // TODO handle errors properly
-      .map_err(|_err| anyhow!("Failed to transform"))?;
+    let path_style = options.diagnostic_path_style;
+    let report_diagnostic = |diagnostic: &Diagnostic| {
+      context.report_diagnostic(
+        &diagnostic
+          .clone()
+          .with_path_style(path_style, context.project_root()),
+      );
+    };
+
+    // The first error fails the transformation, pointing at the offending source code. Warnings
+    // are still reported, as they would otherwise be lost.
+    if let Some(diagnostics) = transformation_result.diagnostics.as_mut() {
+      if let Some(index) = diagnostics
+        .iter()
+        .position(|d| d.severity != parcel_js_swc_core::DiagnosticSeverity::Warning)
+      {
+        let error = conversion::convert_error(&asset, diagnostics.remove(index));
+        let error = error.with_path_style(path_style, context.project_root());
+
+        for diagnostic in diagnostics.drain(..) {
+          if diagnostic.severity == parcel_js_swc_core::DiagnosticSeverity::Warning {
+            report_diagnostic(&conversion::convert_diagnostic(&asset, diagnostic));
+          }
+        }
+
+        return Err(anyhow!(error));
+      }
+    }
+
+    // Only entries are executed directly, so the interpreter is stripped from any other asset
+    if !input.is_entry() {
+      transformation_result.shebang = None;
+    }
+
+    let config = parcel_js_swc_core::Config::default();
+    let mut result = conversion::convert_result(
+      asset,
+      &config,
+      transformation_result,
+      options,
+      context.project_root(),
+      &report_diagnostic,
+    )
+    .map_err(|diagnostics| match diagnostics.into_iter().next() {
+      Some(error) => anyhow!(error.with_path_style(path_style, context.project_root())),
+      None => anyhow!("Failed to transform"),
+    })?;
+
+    if let Some(code_postprocessor) = context.code_postprocessor() {
+      let code = code_postprocessor(result.asset.code.to_string());
+      result.asset.code = Arc::new(Code::from(code));
+    }
+
+    if has_cjs_output_directive {
+      result
+        .diagnostics
+        .push(diagnostic!(DiagnosticBuilder::default()
+          .code_frames(vec![CodeFrame::from(file_path.to_path_buf())])
+          .message(format!(
+            "The \"{}\" directive overrides the output format of this file to CommonJS.",
+            CJS_OUTPUT_DIRECTIVE
+          ))
+          .severity(DiagnosticSeverity::Info)));
+      report_diagnostic(result.diagnostics.last().unwrap());
+    }
+
+    if exceeds_max_inline_helpers {
+      result
+        .diagnostics
+        .push(diagnostic!(DiagnosticBuilder::default()
+          .code_frames(vec![CodeFrame::from(file_path.to_path_buf())])
+          .hints(vec![String::from(
+            "Increase `max_inline_helpers` to keep inlining the helpers into this file."
+          )])
+          .message(format!(
+            "This file needs {} helpers, which is more than the {} that can be inlined, so they are imported from @swc/helpers instead.",
+            inlined_helper_count,
+            options.max_inline_helpers.unwrap_or_default()
+          ))
+          .severity(DiagnosticSeverity::Info)));
+      report_diagnostic(result.diagnostics.last().unwrap());
+    }
+
+    result.diagnostics = result
+      .diagnostics
+      .into_iter()
+      .map(|diagnostic| diagnostic.with_path_style(path_style, context.project_root()))
+      .collect();
 
     Ok(result)
   }
 }
 
+/// Transform `code` as a JavaScript file and list the specifiers it imports, in source order
+///
+/// Each specifier is only listed once. Dependencies that are added by the transformer itself,
+/// such as the ES module helpers, have no location in the source and are left out.
+pub fn transform_and_list_specifiers(
+  code: &str,
+  options: ParcelOptions,
+) -> Result<Vec<String>, Error> {
+  let result = transform_code(String::from(code), options)?;
+
+  let mut dependencies: Vec<_> = result
+    .dependencies
+    .into_iter()
+    .filter_map(|dependency| Some((dependency.loc?, dependency.specifier)))
+    .collect();
+  dependencies.sort_by_key(|(loc, _)| (loc.start.line, loc.start.column));
+
+  let mut seen = HashSet::new();
+  Ok(
+    dependencies
+      .into_iter()
+      .map(|(_, specifier)| specifier)
+      .filter(|specifier| seen.insert(specifier.clone()))
+      .collect(),
+  )
+}
+
+/// What was found while transforming code with [`transform_stream`]
+#[derive(Debug, PartialEq)]
+pub struct TransformMeta {
+  pub dependencies: Vec<Dependency>,
+  pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Transform the JavaScript code read from `reader`, and write the transformed code to `writer`
+///
+/// This is meant for streaming pipelines and CLI filters. The code is still read in full before
+/// it is transformed, as SWC needs the whole file.
+pub fn transform_stream<R: Read, W: Write>(
+  mut reader: R,
+  mut writer: W,
+  options: ParcelOptions,
+) -> Result<TransformMeta, Error> {
+  let mut code = String::new();
+  reader.read_to_string(&mut code)?;
+
+  let result = transform_code(code, options)?;
+  writer.write_all(result.asset.code.bytes())?;
+  writer.flush()?;
+
+  Ok(TransformMeta {
+    dependencies: result.dependencies,
+    diagnostics: result.diagnostics,
+  })
+}
+
+/// Transform `code` as an `index.js` file at the project root, outside of a build
+///
+/// The code is passed in as an asset, so the file-system of the context is not read from.
+fn transform_code(code: String, options: ParcelOptions) -> Result<TransformResult, Error> {
+  let mut context = RunTransformContext::default().with_options(Arc::new(options));
+  let input = TransformationInput::Asset(Asset {
+    code: Arc::new(Code::from(code)),
+    file_path: PathBuf::from("index.js"),
+    ..Asset::default()
+  });
+
+  ParcelJsTransformerPlugin::new().transform(&mut context, input)
+}
+
+/// Whether the directive prologue of `code` contains `directive`
+///
+/// This only looks at the string literal statements at the start of the code, skipping over any
+/// interpreter line and comments.
+fn has_directive(code: &str, directive: &str) -> bool {
+  let mut rest = match code.strip_prefix("#!") {
+    Some(code) => code.split_once('\n').map_or("", |(_, rest)| rest),
+    None => code,
+  };
+
+  loop {
+    rest = rest.trim_start();
+
+    if let Some(comment) = rest.strip_prefix("//") {
+      rest = comment.split_once('\n').map_or("", |(_, rest)| rest);
+      continue;
+    }
+
+    if let Some(comment) = rest.strip_prefix("/*") {
+      rest = comment.split_once("*/").map_or("", |(_, rest)| rest);
+      continue;
+    }
+
+    let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+      return false;
+    };
+
+    let Some((value, after)) = rest[1..].split_once(quote) else {
+      return false;
+    };
+
+    if value == directive {
+      return true;
+    }
+
+    rest = after.trim_start();
+    rest = rest.strip_prefix(';').unwrap_or(rest);
+  }
+}
+
+fn convert_es_version(es_version: EsVersion) -> parcel_js_swc_core::EsVersion {
+  match es_version {
+    EsVersion::Es5 => parcel_js_swc_core::EsVersion::Es5,
+    EsVersion::Es2015 => parcel_js_swc_core::EsVersion::Es2015,
+    EsVersion::Es2016 => parcel_js_swc_core::EsVersion::Es2016,
+    EsVersion::Es2017 => parcel_js_swc_core::EsVersion::Es2017,
+    EsVersion::Es2018 => parcel_js_swc_core::EsVersion::Es2018,
+    EsVersion::Es2019 => parcel_js_swc_core::EsVersion::Es2019,
+    EsVersion::Es2020 => parcel_js_swc_core::EsVersion::Es2020,
+    EsVersion::Es2021 => parcel_js_swc_core::EsVersion::Es2021,
+    EsVersion::Es2022 => parcel_js_swc_core::EsVersion::Es2022,
+    EsVersion::EsNext => parcel_js_swc_core::EsVersion::EsNext,
+  }
+}
+
+fn convert_interop(interop: InteropStrategy) -> parcel_js_swc_core::InteropStrategy {
+  match interop {
+    InteropStrategy::Babel => parcel_js_swc_core::InteropStrategy::Babel,
+    InteropStrategy::Node => parcel_js_swc_core::InteropStrategy::Node,
+    InteropStrategy::None => parcel_js_swc_core::InteropStrategy::None,
+  }
+}
+
 #[cfg(test)]
 mod test {
+  use std::collections::HashMap;
   use std::path::PathBuf;
   use std::sync::Arc;
 
   use parcel_core::plugin::{
-    RunTransformContext, TransformResult, TransformationInput, TransformerPlugin,
+    ExportEntry, InitialAsset, InlineScript, RunTransformContext, TransformResult,
+    TransformationInput, TransformerPlugin,
   };
+  use parcel_core::semaphore::Semaphore;
   use parcel_core::types::{
-    Asset, Code, Dependency, FileType, Location, ParcelOptions, SourceLocation, SpecifierType,
-    Symbol,
+    Asset, BuildMode, Code, CodeSuggestion, DefaultFileType, Dependency, Diagnostic,
+    DiagnosticSeverity, Environment, EnvironmentContext, EsVersion, FileType, InteropStrategy,
+    Location, OutputFormat, ParcelOptions, PathStyle, Priority, SourceLocation, SourceType,
+    SpecifierType, Symbol, TargetSourceMapOptions, TrailingNewline,
   };
   use parcel_filesystem::in_memory_file_system::InMemoryFileSystem;
+  use sourcemap::SourceMap;
 
   use crate::ParcelJsTransformerPlugin;
 
+  use super::{has_directive, transform_and_list_specifiers, transform_stream};
+
   fn empty_asset() -> Asset {
     Asset {
       asset_type: FileType::Js,
@@ -157,6 +512,27 @@ mod test {
     assert_eq!(asset_1.id(), asset_2.id());
   }
 
+  #[test]
+  fn test_transformer_version() {
+    let version = ParcelJsTransformerPlugin::transformer_version();
+    let parts: Vec<&str> = version.split('.').collect();
+
+    assert_eq!(parts.len(), 3);
+    assert!(parts
+      .iter()
+      .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit())));
+  }
+
+  #[test]
+  fn test_cache_key_includes_transformer_version() {
+    let transformer = ParcelJsTransformerPlugin::new();
+    let env = Environment::default();
+    let cache_key = transformer.cache_key(&env);
+
+    assert!(cache_key.starts_with(ParcelJsTransformerPlugin::transformer_version()));
+    assert!(cache_key.ends_with(&env.cache_key()));
+  }
+
   #[test]
   fn test_transformer_on_noop_asset() {
     let source_code = Arc::new(Code::from(String::from("function hello() {}")));
@@ -182,6 +558,10 @@ mod test {
           ..empty_asset()
         },
         dependencies: vec![],
+        diagnostics: vec![],
+        extracted_css: vec![],
+        i18n_keys: vec![],
+        injected_helper_ids: vec![],
         invalidate_on_file_change: vec![]
       }
     );
@@ -215,8 +595,9 @@ exports.hello = function() {};
           column: 26,
         },
       }),
-      placeholder: Some("e83f3db3d6f57ea6".to_string()),
+      placeholder: Some("b9480c9ec8035d26".to_string()),
       source_asset_id: Some(format!("{:016x}", asset_id)),
+      source_asset_type: Some(FileType::Js),
       source_path: Some(PathBuf::from("mock_path.js")),
       specifier: String::from("other"),
       specifier_type: SpecifierType::CommonJS,
@@ -228,7 +609,7 @@ exports.hello = function() {};
       }],
       ..Default::default()
     }];
-    expected_dependencies[0].set_placeholder("e83f3db3d6f57ea6");
+    expected_dependencies[0].set_placeholder("b9480c9ec8035d26");
     expected_dependencies[0].set_kind("Require");
 
     assert_eq!(result.dependencies, expected_dependencies);
@@ -240,7 +621,7 @@ exports.hello = function() {};
           asset_type: FileType::Js,
           // SWC inserts a newline here
           code: Arc::new(Code::from(String::from(
-            "const x = require(\"e83f3db3d6f57ea6\");\nexports.hello = function() {};\n"
+            "const x = require(\"b9480c9ec8035d26\");\nexports.hello = function() {};\n"
           ))),
           symbols: vec![
             Symbol {
@@ -258,11 +639,7 @@ exports.hello = function() {};
             },
             Symbol {
               exported: String::from("*"),
-              loc: Some(SourceLocation {
-                file_path: PathBuf::from("mock_path.js"),
-                start: Location { line: 1, column: 1 },
-                end: Location { line: 1, column: 1 }
-              }),
+              loc: None,
               local: String::from("$_"),
               ..Default::default()
             },
@@ -278,17 +655,2437 @@ exports.hello = function() {};
           ..empty_asset()
         },
         dependencies: expected_dependencies,
+        diagnostics: vec![],
+        extracted_css: vec![],
+        i18n_keys: vec![],
+        injected_helper_ids: vec![],
         invalidate_on_file_change: vec![]
       }
     );
   }
 
-  fn run_test(asset: Asset) -> anyhow::Result<TransformResult> {
-    let file_system = Arc::new(InMemoryFileSystem::default());
-    let options = Arc::new(ParcelOptions::default());
+  #[test]
+  fn test_transform_and_list_specifiers() {
+    let code = r#"
+import a from './a';
+import { b } from './b';
+import { c } from './a';
+const d = require('d');
+export const e = import('./e');
+"#;
+
+    assert_eq!(
+      transform_and_list_specifiers(code, ParcelOptions::default()).unwrap(),
+      vec!["./a", "./b", "d", "./e"]
+    );
+  }
+
+  #[test]
+  fn test_side_effect_only_import() {
+    for should_scope_hoist in [false, true] {
+      let target_asset = Asset {
+        code: Arc::new(Code::from(String::from(
+          "import 'polyfill';\nimport { a } from 'other';\nconsole.log(a);",
+        ))),
+        env: Arc::new(Environment {
+          should_scope_hoist,
+          ..Environment::default()
+        }),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      };
+
+      let result = run_test(target_asset).unwrap();
+      let dependency = |specifier: &str| {
+        result
+          .dependencies
+          .iter()
+          .find(|dependency| dependency.specifier == specifier)
+          .unwrap()
+      };
+
+      assert!(dependency("polyfill").side_effect_only);
+      assert_eq!(dependency("polyfill").symbols, Vec::new());
+      assert!(!dependency("other").side_effect_only);
+      assert!(!dependency("other").symbols.is_empty());
+    }
+  }
+
+  #[test]
+  fn test_transform_stream() {
+    let input: &[u8] = b"const x = require('other');";
+    let mut output = Vec::new();
+
+    let meta = transform_stream(input, &mut output, ParcelOptions::default()).unwrap();
+
+    assert_eq!(meta.diagnostics, Vec::new());
+    assert_eq!(meta.dependencies.len(), 1);
+    assert_eq!(meta.dependencies[0].specifier, "other");
+    assert_eq!(meta.dependencies[0].specifier_type, SpecifierType::CommonJS);
+
+    // The specifier is replaced with the placeholder of the dependency
+    let placeholder = meta.dependencies[0].placeholder.clone().unwrap();
+    assert_eq!(
+      String::from_utf8(output).unwrap(),
+      format!("const x = require(\"{}\");\n", placeholder)
+    );
+  }
+
+  #[test]
+  fn test_into_asset_with_deps() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "const x = require('other');\nexports.hello = function() {};",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test(target_asset).unwrap();
+    let expected_asset = result.asset.clone();
+    let expected_dependencies = result.dependencies.clone();
+
+    let asset_with_deps = result.into_asset_with_deps();
+
+    assert_eq!(asset_with_deps.asset, expected_asset);
+    assert_eq!(asset_with_deps.dependencies, expected_dependencies);
+    assert_eq!(asset_with_deps.dependencies.len(), 1);
+    assert_eq!(asset_with_deps.dependencies[0].specifier, "other");
+    // Dependencies refer to the id of the asset before it was given its unique key
+    assert_eq!(
+      asset_with_deps.dependencies[0].source_asset_id,
+      asset_with_deps.asset.unique_key
+    );
+  }
+
+  #[test]
+  fn test_export_report() {
+    let result = run_test(Asset {
+      code: Arc::new(Code::from(String::from(
+        "const x = require('other');\nexports.hello = function() {};",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    })
+    .unwrap();
+
+    assert!(result
+      .asset
+      .symbols
+      .iter()
+      .any(|symbol| symbol.exported == "*"));
+    assert_eq!(
+      result.export_report(),
+      vec![ExportEntry {
+        name: String::from("hello"),
+        local: String::from("$hello"),
+        loc: Some(SourceLocation {
+          file_path: PathBuf::from("mock_path.js"),
+          start: Location { line: 2, column: 9 },
+          end: Location {
+            line: 2,
+            column: 14
+          }
+        }),
+      }]
+    );
+  }
+
+  #[test]
+  fn test_unique_key_includes_target_name() {
+    let source_code = Arc::new(Code::from(String::from("function hello() {}")));
+    let make_asset = |target_name: &str| Asset {
+      code: source_code.clone(),
+      env: Arc::new(Environment {
+        target_name: Some(String::from(target_name)),
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+
+    let modern_asset = make_asset("modern");
+    let legacy_asset = make_asset("legacy");
+    assert_eq!(modern_asset.id(), legacy_asset.id());
+
+    let asset_id = modern_asset.id();
+    let modern_result = run_test(modern_asset).unwrap();
+    let legacy_result = run_test(legacy_asset).unwrap();
+
+    assert_eq!(
+      modern_result.asset.unique_key,
+      Some(format!("modern:{:016x}", asset_id))
+    );
+    assert_eq!(
+      legacy_result.asset.unique_key,
+      Some(format!("legacy:{:016x}", asset_id))
+    );
+  }
+
+  fn run_scope_hoisted(code: &str, should_scope_hoist: bool) -> anyhow::Result<TransformResult> {
+    run_test(Asset {
+      code: Arc::new(Code::from(String::from(code))),
+      env: Arc::new(Environment {
+        should_scope_hoist,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    })
+  }
+
+  #[test]
+  fn test_referenced_exports_are_not_duplicates() {
+    assert!(run_scope_hoisted("export const a = 1;\nconsole.log(a);", true).is_ok());
+    assert!(run_scope_hoisted("exports.foo = 1;\nexports.foo = 2;", true).is_ok());
+  }
+
+  #[test]
+  fn test_duplicate_export_fails_the_transformation() {
+    for should_scope_hoist in [true, false] {
+      let error = run_scope_hoisted(
+        "export const a = 1;\nconst b = 2;\nexport { b as a };",
+        should_scope_hoist,
+      )
+      .unwrap_err()
+      .downcast::<Diagnostic>()
+      .unwrap();
+
+      assert_eq!(error.message, "Duplicate export 'a'.");
+
+      let highlights = &error.code_frames[0].code_highlights;
+      assert_eq!(highlights.len(), 2);
+      assert_eq!(highlights[0].start.line, 1);
+      assert_eq!(highlights[1].start.line, 3);
+    }
+  }
+
+  #[test]
+  fn test_unique_key_uses_name_delimiter() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from("function hello() {}"))),
+      env: Arc::new(Environment {
+        target_name: Some(String::from("modern")),
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+
+    let asset_id = target_asset.id();
+    let result = run_test_with_options(
+      target_asset,
+      ParcelOptions {
+        name_delimiter: Some('~'),
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap();
+
+    assert_eq!(
+      result.asset.unique_key,
+      Some(format!("modern~{:016x}", asset_id))
+    );
+  }
+
+  #[test]
+  fn test_service_worker_registration_creates_dependency() {
+    let source_code = Arc::new(Code::from(String::from(
+      "navigator.serviceWorker.register(new URL('./sw.js', import.meta.url));",
+    )));
+    let target_asset = Asset {
+      code: source_code,
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test(target_asset).unwrap();
+
+    assert_eq!(result.dependencies.len(), 1);
+
+    let dependency = &result.dependencies[0];
+    assert_eq!(dependency.specifier, "./sw.js");
+    assert_eq!(dependency.specifier_type, SpecifierType::Url);
+    assert_eq!(dependency.env.context, EnvironmentContext::ServiceWorker);
+    assert_eq!(dependency.env.output_format, OutputFormat::Global);
+    assert!(dependency.needs_stable_name);
+  }
+
+  #[test]
+  fn test_keep_names_preserves_function_names_when_minifying() {
+    let source_code = Arc::new(Code::from(String::from(
+      r#"
+(function () {
+  function myFunc() {}
+  const localVariable = myFunc;
+  console.log(localVariable);
+})();
+    "#,
+    )));
+    let target_asset = Asset {
+      code: source_code,
+      env: Arc::new(Environment {
+        should_optimize: true,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+
+    // Optimized environments are minified by the optimizers, rather than the transformer
+    let code = run_test(target_asset.clone()).unwrap().asset.code;
+    assert!(code.to_string().contains("function myFunc()"));
+    assert!(code.to_string().contains("localVariable"));
+
+    let minified = run_test_with_options(
+      target_asset.clone(),
+      ParcelOptions {
+        minify: true,
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap()
+    .asset
+    .code;
+    assert!(!minified.to_string().contains("myFunc"));
+    assert!(!minified.to_string().contains("localVariable"));
+
+    let minified = run_test_with_options(
+      target_asset,
+      ParcelOptions {
+        keep_names: true,
+        minify: true,
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap()
+    .asset
+    .code;
+    assert!(minified.to_string().contains("function myFunc()"));
+    assert!(!minified.to_string().contains("localVariable"));
+  }
+
+  #[test]
+  fn test_concatenated_require_specifier_is_static() {
+    let result = run_test(Asset {
+      code: Arc::new(Code::from(String::from(
+        "const sub = require('pkg' + '/sub');",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    })
+    .unwrap();
+
+    assert_eq!(result.diagnostics, vec![]);
+    assert_eq!(result.dependencies.len(), 1);
+    assert_eq!(result.dependencies[0].specifier, "pkg/sub");
+    assert_eq!(
+      result.dependencies[0].specifier_type,
+      SpecifierType::CommonJS
+    );
+  }
+
+  #[test]
+  fn test_computed_require_reports_warning() {
+    let source_code = Arc::new(Code::from(String::from("const y = require(x);")));
+    let target_asset = Asset {
+      code: source_code,
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test(target_asset).unwrap();
+
+    assert_eq!(result.dependencies, vec![]);
+    assert_eq!(result.diagnostics.len(), 1);
+
+    let diagnostic = &result.diagnostics[0];
+    assert_eq!(diagnostic.severity, DiagnosticSeverity::Warning);
+    assert_eq!(
+      diagnostic.message,
+      "Computed `require` calls cannot be statically resolved."
+    );
+    assert_eq!(
+      diagnostic.code_frames[0].code_highlights[0].start,
+      Location {
+        line: 1,
+        column: 11
+      }
+    );
+    assert_eq!(
+      diagnostic.code_frames[0].code_highlights[0].end,
+      Location {
+        line: 1,
+        column: 20
+      }
+    );
+  }
+
+  #[test]
+  fn test_env_override_takes_precedence_over_asset_env() {
+    let source_code = Arc::new(Code::from(String::from(
+      "import x from 'other';\nexport default x;",
+    )));
+    let target_asset = Asset {
+      code: source_code,
+      env: Arc::new(Environment {
+        output_format: OutputFormat::CommonJS,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let env_override = Arc::new(Environment {
+      output_format: OutputFormat::EsModule,
+      ..(*target_asset.env).clone()
+    });
+
+    let file_system = Arc::new(InMemoryFileSystem::default());
+    let options = Arc::new(ParcelOptions::default());
+    let mut context = RunTransformContext::new(file_system, options, PathBuf::default());
+    let mut transformer = ParcelJsTransformerPlugin::new();
+    let input = TransformationInput::Asset(target_asset).with_env_override(env_override);
+
+    let result = transformer.transform(&mut context, input).unwrap();
+
+    assert_eq!(result.asset.env.output_format, OutputFormat::EsModule);
+    assert_eq!(result.asset.file_path, PathBuf::from("mock_path.js"));
+  }
+
+  #[test]
+  fn test_deterministic_symbol_order() {
+    let source_code = Arc::new(Code::from(String::from(
+      r#"
+import { z, y } from 'other';
+export const b = z;
+export const a = y;
+    "#,
+    )));
+    let target_asset = Asset {
+      code: source_code,
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let options = ParcelOptions {
+      deterministic_symbol_order: true,
+      ..ParcelOptions::default()
+    };
+
+    let result_1 = run_test_with_options(target_asset.clone(), options.clone()).unwrap();
+    let result_2 = run_test_with_options(target_asset, options).unwrap();
+
+    let exported_names = |symbols: &[Symbol]| {
+      symbols
+        .iter()
+        .map(|symbol| symbol.exported.clone())
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(result_1.asset.symbols, result_2.asset.symbols);
+    assert_eq!(exported_names(&result_1.asset.symbols), vec!["a", "b"]);
+    assert_eq!(result_1.dependencies, result_2.dependencies);
+
+    let dependency = result_1
+      .dependencies
+      .iter()
+      .find(|dependency| dependency.specifier == "other")
+      .unwrap();
+    assert_eq!(exported_names(&dependency.symbols), vec!["y", "z"]);
+  }
+
+  #[test]
+  fn test_ambient_declarations_are_removed() {
+    let source_code = Arc::new(Code::from(String::from(
+      r#"
+declare module 'x' {
+  import { y } from 'y';
+  export const value: string;
+}
+declare const ambient: number;
+export const hello: string = 'world';
+    "#,
+    )));
+    let target_asset = Asset {
+      code: source_code,
+      file_path: "mock_path.ts".into(),
+      ..Asset::default()
+    };
+    let result = run_test(target_asset).unwrap();
+    let code = result.asset.code.to_string();
+
+    assert!(!result
+      .dependencies
+      .iter()
+      .any(|dependency| dependency.specifier == "x" || dependency.specifier == "y"));
+    assert!(!code.contains("declare"));
+    assert!(!code.contains("ambient"));
+    assert!(!code.contains("value"));
+    assert!(code.contains("world"));
+  }
+
+  #[test]
+  fn test_typescript_import_require_and_export_assignment() {
+    let make_asset = |should_scope_hoist: bool| Asset {
+      code: Arc::new(Code::from(String::from(
+        "import x = require('y');\nexport = x;",
+      ))),
+      env: Arc::new(Environment {
+        should_scope_hoist,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.ts".into(),
+      ..Asset::default()
+    };
+
+    for should_scope_hoist in [false, true] {
+      let result = run_test(make_asset(should_scope_hoist)).unwrap();
+      let dependency = result
+        .dependencies
+        .iter()
+        .find(|dependency| dependency.specifier == "y")
+        .unwrap();
+
+      assert_eq!(dependency.specifier_type, SpecifierType::CommonJS);
+      assert!(result
+        .asset
+        .symbols
+        .iter()
+        .any(|symbol| symbol.exported == "*"));
+      assert!(!result.asset.code.to_string().contains("export ="));
+    }
+  }
+
+  #[test]
+  fn test_unknown_extension_uses_default_file_type() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from("function hello() {}"))),
+      file_path: "mock_path.foo".into(),
+      ..Asset::default()
+    };
+    let result = run_test(target_asset).unwrap();
+
+    assert_eq!(result.asset.asset_type, FileType::Js);
+    assert_eq!(
+      result.asset.code,
+      Arc::new(Code::from(String::from("function hello() {}\n")))
+    );
+  }
+
+  #[test]
+  fn test_unknown_extension_with_error_policy() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from("function hello() {}"))),
+      file_path: "mock_path.foo".into(),
+      ..Asset::default()
+    };
+    let error = run_test_with_options(
+      target_asset,
+      ParcelOptions {
+        default_file_type: DefaultFileType::Error,
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap_err();
+
+    let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+    assert_eq!(diagnostic.message, "Unknown file extension \"foo\".");
+    assert_eq!(
+      diagnostic.code_frames[0].path,
+      Some(PathBuf::from("mock_path.foo"))
+    );
+  }
+
+  #[test]
+  fn test_default_export_symbol_location() {
+    let make_asset = |code: &str, should_scope_hoist: bool| Asset {
+      code: Arc::new(Code::from(String::from(code))),
+      env: Arc::new(Environment {
+        should_scope_hoist,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let default_symbol_loc = |result: TransformResult| {
+      result
+        .asset
+        .symbols
+        .into_iter()
+        .find(|symbol| symbol.exported == "default")
+        .and_then(|symbol| symbol.loc)
+        .unwrap()
+    };
+    let expected_loc = SourceLocation {
+      file_path: PathBuf::from("mock_path.js"),
+      start: Location { line: 2, column: 8 },
+      end: Location {
+        line: 2,
+        column: 15,
+      },
+    };
+
+    for should_scope_hoist in [false, true] {
+      let result = run_test(make_asset(
+        "const x = 1;\nexport default function () {}",
+        should_scope_hoist,
+      ))
+      .unwrap();
+      assert_eq!(default_symbol_loc(result), expected_loc);
+
+      let result = run_test(make_asset(
+        "const x = 1;\nexport default x;",
+        should_scope_hoist,
+      ))
+      .unwrap();
+      assert_eq!(default_symbol_loc(result), expected_loc);
+    }
+  }
+
+  #[test]
+  fn test_decorated_default_export_class() {
+    let make_asset = || Asset {
+      code: Arc::new(Code::from(String::from(
+        "function dec(target) { return target; }\nexport default @dec class {}",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+
+    assert!(run_test(make_asset()).is_err());
+
+    let result = run_test_with_options(
+      make_asset(),
+      ParcelOptions {
+        decorators: true,
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap();
+
+    assert!(result
+      .asset
+      .symbols
+      .iter()
+      .any(|symbol| symbol.exported == "default"));
+
+    let code = result.asset.code.to_string();
+    assert!(code.contains("_ts_decorate"));
+    assert!(code.contains("dec"));
+  }
+
+  #[test]
+  fn test_export_all_symbol_location() {
+    let make_asset = |should_scope_hoist: bool| Asset {
+      code: Arc::new(Code::from(String::from(
+        "const x = 1;\n  export * from './other';",
+      ))),
+      env: Arc::new(Environment {
+        should_scope_hoist,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+
+    for should_scope_hoist in [false, true] {
+      let result = run_test(make_asset(should_scope_hoist)).unwrap();
+      let symbol = result
+        .dependencies
+        .iter()
+        .find(|dependency| dependency.specifier == "./other")
+        .and_then(|dependency| {
+          dependency
+            .symbols
+            .iter()
+            .find(|symbol| symbol.exported == "*")
+        })
+        .unwrap();
+
+      assert_eq!(
+        symbol.loc,
+        Some(SourceLocation {
+          file_path: PathBuf::from("mock_path.js"),
+          start: Location { line: 2, column: 3 },
+          end: Location { line: 2, column: 9 },
+        })
+      );
+    }
+  }
+
+  #[test]
+  fn test_banner_is_prepended() {
+    let make_asset = |source_map: Option<TargetSourceMapOptions>| Asset {
+      code: Arc::new(Code::from(String::from("require('other');"))),
+      env: Arc::new(Environment {
+        source_map,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let options = ParcelOptions {
+      banner: Some(String::from("/* banner\n * license */")),
+      ..ParcelOptions::default()
+    };
+
+    let result = run_test_with_options(make_asset(None), options.clone()).unwrap();
+    assert!(result
+      .asset
+      .code
+      .to_string()
+      .starts_with("/* banner\n * license */\nrequire("));
+    assert_eq!(result.asset.map, None);
+    assert_eq!(result.dependencies[0].loc.as_ref().unwrap().start.line, 1);
+
+    let result =
+      run_test_with_options(make_asset(Some(TargetSourceMapOptions::default())), options).unwrap();
+    assert!(result
+      .asset
+      .code
+      .to_string()
+      .starts_with("/* banner\n * license */\nrequire("));
+
+    // The generated code moves down by two lines, while the original source stays the same
+    let map = SourceMap::from_slice(result.asset.map.unwrap().as_bytes()).unwrap();
+    let token = map.get_token(0).unwrap();
+    assert_eq!((token.get_dst_line(), token.get_src_line()), (2, 0));
+    assert_eq!(result.dependencies[0].loc.as_ref().unwrap().start.line, 1);
+  }
+
+  #[test]
+  fn test_emit_module_id_comment() {
+    let make_asset = |source_map: Option<TargetSourceMapOptions>| Asset {
+      code: Arc::new(Code::from(String::from("require('other');"))),
+      env: Arc::new(Environment {
+        source_map,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      unique_key: Some(String::from("mock-key")),
+      ..Asset::default()
+    };
+    let options = ParcelOptions {
+      banner: Some(String::from("/* license */")),
+      emit_module_id_comment: true,
+      ..ParcelOptions::default()
+    };
+
+    let result = run_test_with_options(make_asset(None), options.clone()).unwrap();
+    assert!(result
+      .asset
+      .code
+      .to_string()
+      .starts_with("/* module: mock-key */\n/* license */\nrequire("));
+
+    let result =
+      run_test_with_options(make_asset(Some(TargetSourceMapOptions::default())), options).unwrap();
+    assert!(result
+      .asset
+      .code
+      .to_string()
+      .starts_with("/* module: mock-key */\n"));
+
+    let map = SourceMap::from_slice(result.asset.map.unwrap().as_bytes()).unwrap();
+    assert_eq!(map.get_token(0).unwrap().get_dst_line(), 2);
+    assert_eq!(result.dependencies[0].loc.as_ref().unwrap().start.line, 1);
+  }
+
+  #[test]
+  fn test_require_within_try_is_optional() {
+    let source_code = Arc::new(Code::from(String::from(
+      r#"
+const required = require('required-dep');
+try {
+  require('optional-dep');
+} catch (err) {}
+    "#,
+    )));
+    let target_asset = Asset {
+      code: source_code,
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test(target_asset).unwrap();
+
+    let is_optional = |specifier: &str| {
+      result
+        .dependencies
+        .iter()
+        .find(|dependency| dependency.specifier == specifier)
+        .map(|dependency| dependency.is_optional)
+        .unwrap()
+    };
+
+    assert!(!is_optional("required-dep"));
+    assert!(is_optional("optional-dep"));
+  }
+
+  #[test]
+  fn test_default_import_of_commonjs_module_reports_warning() {
+    let source_code = Arc::new(Code::from(String::from(
+      r#"
+import foo from './cjs-module.cjs';
+import bar from './esm-module.mjs';
+console.log(foo, bar);
+    "#,
+    )));
+    let target_asset = Asset {
+      code: source_code,
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test(target_asset).unwrap();
+
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(
+      result.diagnostics[0].message,
+      "Default import of CommonJS module './cjs-module.cjs' relies on interop."
+    );
+    assert_eq!(
+      result.diagnostics[0].code_frames[0].code_highlights[0]
+        .start
+        .line,
+      2
+    );
+  }
+
+  #[test]
+  fn test_allow_jsx_in_ts() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from("export const App = () => <div/>;"))),
+      file_path: "mock_path.ts".into(),
+      ..Asset::default()
+    };
+    let options = |allow_jsx_in_ts: bool| ParcelOptions {
+      allow_jsx_in_ts,
+      ..ParcelOptions::default()
+    };
+
+    let result = run_test_with_options(target_asset.clone(), options(true)).unwrap();
+    let code = result.asset.code.to_string();
+
+    // As with any TypeScript, the output is JavaScript
+    assert_eq!(result.asset.asset_type, FileType::Js);
+    assert!(!code.contains("<div/>"));
+    assert!(code.contains("createElement"));
+
+    assert!(run_test_with_options(target_asset, options(false)).is_err());
+  }
+
+  #[test]
+  fn test_automatic_jsx_runtime_redundant_react_import() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "import React from 'react';\nexport const App = () => <div>Hello</div>;",
+      ))),
+      file_path: "mock_path.jsx".into(),
+      ..Asset::default()
+    };
+    // Development builds import `react/jsx-dev-runtime` instead
+    let options = |automatic_jsx_runtime: bool| ParcelOptions {
+      automatic_jsx_runtime,
+      mode: BuildMode::Production,
+      ..ParcelOptions::default()
+    };
+
+    let result = run_test_with_options(target_asset.clone(), options(true)).unwrap();
+    let code = result.asset.code.to_string();
+
+    assert!(!code.contains("React.createElement"));
+    assert!(result
+      .dependencies
+      .iter()
+      .any(|dependency| dependency.specifier == "react/jsx-runtime"));
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(
+      result.diagnostics[0].message,
+      "`React` is imported from 'react' but is not needed by the automatic JSX runtime."
+    );
+
+    // The classic runtime references React, so the import is needed
+    let result = run_test_with_options(target_asset, options(false)).unwrap();
+    assert_eq!(result.diagnostics, Vec::new());
+  }
+
+  #[test]
+  fn test_automatic_jsx_runtime_development_transform() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "const x = 1;\nexport const App = () => <div>Hello</div>;",
+      ))),
+      file_path: "mock_path.jsx".into(),
+      ..Asset::default()
+    };
+    let options = |mode: BuildMode| ParcelOptions {
+      automatic_jsx_runtime: true,
+      mode,
+      ..ParcelOptions::default()
+    };
+    let has_dependency = |result: &TransformResult, specifier: &str| {
+      result
+        .dependencies
+        .iter()
+        .any(|dependency| dependency.specifier == specifier)
+    };
+
+    let result =
+      run_test_with_options(target_asset.clone(), options(BuildMode::Development)).unwrap();
+    let code = result.asset.code.to_string();
+
+    assert!(has_dependency(&result, "react/jsx-dev-runtime"));
+    assert!(code.contains("jsxDEV"));
+    assert!(code.contains("lineNumber: 2"));
+
+    let result = run_test_with_options(target_asset, options(BuildMode::Production)).unwrap();
+    let code = result.asset.code.to_string();
+
+    assert!(has_dependency(&result, "react/jsx-runtime"));
+    assert!(!has_dependency(&result, "react/jsx-dev-runtime"));
+    assert!(!code.contains("jsxDEV"));
+    assert!(!code.contains("lineNumber"));
+  }
+
+  #[test]
+  fn test_i18n_keys_are_collected() {
+    let source_code = Arc::new(Code::from(String::from(
+      r#"
+const greeting = t('hello');
+const other = i18n.t('other', { count: 1 });
+const ignored = translate('ignored');
+    "#,
+    )));
+    let target_asset = Asset {
+      code: source_code,
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test_with_options(
+      target_asset,
+      ParcelOptions {
+        i18n_functions: vec![String::from("t"), String::from("i18n.t")],
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap();
+
+    assert_eq!(
+      result.i18n_keys,
+      vec![
+        (
+          String::from("hello"),
+          SourceLocation {
+            file_path: PathBuf::from("mock_path.js"),
+            start: Location {
+              line: 2,
+              column: 20
+            },
+            end: Location {
+              line: 2,
+              column: 27
+            },
+          }
+        ),
+        (
+          String::from("other"),
+          SourceLocation {
+            file_path: PathBuf::from("mock_path.js"),
+            start: Location {
+              line: 3,
+              column: 22
+            },
+            end: Location {
+              line: 3,
+              column: 29
+            },
+          }
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_obfuscate_exports() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "export function helper() {}\nexport function publicApi() {\n  return helper();\n}",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test_with_options(
+      target_asset,
+      ParcelOptions {
+        obfuscate_exports: Some(vec![String::from("publicApi")]),
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap();
+
+    let local = |exported: &str| {
+      result
+        .asset
+        .symbols
+        .iter()
+        .find(|symbol| symbol.exported == exported)
+        .map(|symbol| symbol.local.clone())
+        .unwrap()
+    };
+
+    assert_eq!(local("publicApi"), "$publicApi");
+    assert_ne!(local("helper"), "$helper");
+
+    let code = result.asset.code.to_string();
+    let helper = local("helper").strip_prefix('$').unwrap().to_string();
+
+    assert!(code.contains("function publicApi()"));
+    assert!(!code.contains("function helper()"));
+    assert!(code.contains(&format!("function {}()", helper)));
+    assert!(code.contains(&format!("return {}();", helper)));
+    assert!(code.contains("\"helper\""));
+  }
+
+  #[test]
+  fn test_extract_css_in_js() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "const Title = styled.div`color:red`;\nconst other = html`<div></div>`;",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test_with_options(
+      target_asset,
+      ParcelOptions {
+        extract_css_in_js: true,
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap();
+
+    assert_eq!(
+      result.extracted_css,
+      vec![(
+        String::from("color:red"),
+        SourceLocation {
+          file_path: PathBuf::from("mock_path.js"),
+          start: Location {
+            line: 1,
+            column: 25
+          },
+          end: Location {
+            line: 1,
+            column: 36
+          },
+        }
+      )]
+    );
+  }
+
+  #[test]
+  fn test_assume_esm_parses_scripts_as_modules() {
+    let source_code = Arc::new(Code::from(String::from(
+      r#"
+const x = require('other');
+export const hello = x;
+    "#,
+    )));
+    let target_asset = Asset {
+      code: source_code,
+      env: Arc::new(Environment {
+        source_type: SourceType::Script,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+
+    // Exports are not allowed within scripts
+    assert!(run_test(target_asset.clone()).is_err());
+
+    let result = run_test_with_options(
+      target_asset,
+      ParcelOptions {
+        assume_esm: true,
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap();
+
+    assert_eq!(result.asset.env.source_type, SourceType::Module);
+    assert!(result
+      .asset
+      .symbols
+      .iter()
+      .any(|symbol| symbol.exported == "hello"));
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(
+      result.diagnostics[0].message,
+      "Unexpected `require('other')` in a file that is assumed to be an ES module."
+    );
+  }
+
+  #[test]
+  fn test_computed_dynamic_import_with_strict_dynamic_imports() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from("import(x);"))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+
+    let result = run_test(target_asset.clone()).unwrap();
+
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(
+      result.diagnostics[0].message,
+      "Computed dynamic imports cannot be statically resolved."
+    );
+
+    let result = run_test_with_options(
+      target_asset,
+      ParcelOptions {
+        strict_dynamic_imports: true,
+        ..ParcelOptions::default()
+      },
+    );
+
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn test_diagnostic_path_style() {
+    let run = |diagnostic_path_style: PathStyle| {
+      let options = Arc::new(ParcelOptions {
+        diagnostic_path_style,
+        ..ParcelOptions::default()
+      });
+      let mut context = RunTransformContext::new(
+        Arc::new(InMemoryFileSystem::default()),
+        options,
+        PathBuf::from("/project"),
+      );
+      let input = TransformationInput::Asset(Asset {
+        code: Arc::new(Code::from(String::from("import(x);"))),
+        file_path: "/project/src/index.js".into(),
+        ..Asset::default()
+      });
+
+      let result = ParcelJsTransformerPlugin::new()
+        .transform(&mut context, input)
+        .unwrap();
+
+      result.diagnostics[0].code_frames[0].path.clone()
+    };
+
+    assert_eq!(
+      run(PathStyle::Absolute),
+      Some(PathBuf::from("/project/src/index.js"))
+    );
+    assert_eq!(
+      run(PathStyle::Relative),
+      Some(PathBuf::from("src/index.js"))
+    );
+  }
+
+  #[test]
+  fn test_diagnostics_are_streamed() {
+    let (diagnostics_tx, diagnostics_rx) = std::sync::mpsc::channel();
+    let mut context = RunTransformContext::new(
+      Arc::new(InMemoryFileSystem::default()),
+      Arc::new(ParcelOptions::default()),
+      PathBuf::default(),
+    )
+    .with_diagnostics_sender(diagnostics_tx);
+    let input = TransformationInput::Asset(Asset {
+      code: Arc::new(Code::from(String::from("import(x);"))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    });
+
+    let result = ParcelJsTransformerPlugin::new()
+      .transform(&mut context, input)
+      .unwrap();
+
+    let streamed = diagnostics_rx.try_iter().collect::<Vec<Diagnostic>>();
+    assert_eq!(streamed.len(), 1);
+    assert_eq!(
+      streamed[0].message,
+      "Computed dynamic imports cannot be statically resolved."
+    );
+    assert_eq!(streamed, result.diagnostics);
+  }
+
+  #[test]
+  fn test_diagnostics_are_streamed_when_the_transformation_fails() {
+    let (diagnostics_tx, diagnostics_rx) = std::sync::mpsc::channel();
+    let mut context = RunTransformContext::new(
+      Arc::new(InMemoryFileSystem::default()),
+      Arc::new(ParcelOptions::default()),
+      PathBuf::default(),
+    )
+    .with_diagnostics_sender(diagnostics_tx);
+    let input = TransformationInput::Asset(Asset {
+      code: Arc::new(Code::from(String::from(
+        "import(x);\nexport const a = 1;\nconst b = 2;\nexport { b as a };",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    });
+
+    let error = ParcelJsTransformerPlugin::new()
+      .transform(&mut context, input)
+      .unwrap_err();
+
+    assert_eq!(
+      error.downcast_ref::<Diagnostic>().unwrap().message,
+      "Duplicate export 'a'."
+    );
+
+    let streamed = diagnostics_rx.try_iter().collect::<Vec<Diagnostic>>();
+    assert_eq!(streamed.len(), 1);
+    assert_eq!(
+      streamed[0].message,
+      "Computed dynamic imports cannot be statically resolved."
+    );
+  }
+
+  #[test]
+  fn test_with_statement_in_module_is_a_descriptive_error() {
+    let error = run_test(Asset {
+      code: Arc::new(Code::from(String::from(
+        "import a from './a';\nwith (a) {\n  b();\n}",
+      ))),
+      env: Arc::new(Environment {
+        source_type: SourceType::Module,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    })
+    .unwrap_err();
+
+    let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+    assert_eq!(diagnostic.severity, DiagnosticSeverity::Error);
+    assert!(diagnostic.message.contains("strict mode"));
+    assert!(diagnostic.hints[0].starts_with("ES modules are always in strict mode."));
+
+    let code_frame = &diagnostic.code_frames[0];
+    assert_eq!(code_frame.path, Some(PathBuf::from("mock_path.js")));
+    assert_eq!(code_frame.code_highlights[0].start.line, 2);
+    assert_eq!(code_frame.code_highlights[0].start.column, 1);
+  }
+
+  #[test]
+  fn test_mjs_files_are_parsed_as_modules() {
+    let result = run_test(Asset {
+      code: Arc::new(Code::from(String::from("export const a = 1;"))),
+      env: Arc::new(Environment {
+        source_type: SourceType::Script,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.mjs".into(),
+      ..Asset::default()
+    })
+    .unwrap();
+
+    assert_eq!(result.asset.env.source_type, SourceType::Module);
+    assert!(result
+      .asset
+      .symbols
+      .iter()
+      .any(|symbol| symbol.exported == "a"));
+  }
+
+  #[test]
+  fn test_follow_fetch_urls() {
+    let make_asset = || Asset {
+      code: Arc::new(Code::from(String::from(
+        "fetch('./data.json');\nfetch('https://example.com/data.json');\nfetch(url);",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+
+    assert_eq!(run_test(make_asset()).unwrap().dependencies, vec![]);
+
+    let result = run_test_with_options(
+      make_asset(),
+      ParcelOptions {
+        follow_fetch_urls: true,
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap();
+
+    assert_eq!(result.dependencies.len(), 1);
+    assert_eq!(result.dependencies[0].specifier, "./data.json");
+    assert_eq!(result.dependencies[0].specifier_type, SpecifierType::Url);
+    assert!(!result.asset.code.to_string().contains("./data.json"));
+  }
+
+  #[test]
+  fn test_target_es_version() {
+    let run = |target_es_version: EsVersion| {
+      let asset = Asset {
+        code: Arc::new(Code::from(String::from(
+          "export async function load() {\n  return await fetch(url);\n}",
+        ))),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      };
+      let options = ParcelOptions {
+        target_es_version: Some(target_es_version),
+        ..ParcelOptions::default()
+      };
+
+      run_test_with_options(asset, options)
+        .unwrap()
+        .asset
+        .code
+        .to_string()
+    };
+
+    assert!(!run(EsVersion::Es2015).contains("async function"));
+    assert!(run(EsVersion::Es2022).contains("async function"));
+
+    let es5 = run(EsVersion::Es5);
+    assert!(!es5.contains("async function"));
+    assert!(!es5.contains("=>"));
+  }
+
+  #[test]
+  fn test_interop_strategy() {
+    let run = |interop: InteropStrategy| {
+      let asset = Asset {
+        code: Arc::new(Code::from(String::from("import foo from './dep';\nfoo();"))),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      };
+      let options = ParcelOptions {
+        interop,
+        ..ParcelOptions::default()
+      };
+
+      run_test_with_options(asset, options)
+        .unwrap()
+        .asset
+        .code
+        .to_string()
+    };
+
+    let babel = run(InteropStrategy::Babel);
+    assert!(babel.contains("var _depDefault = parcelHelpers.interopDefault(_dep);"));
+    assert!(babel.contains("(0, _depDefault.default)();"));
+
+    let node = run(InteropStrategy::Node);
+    assert!(!node.contains("interopDefault"));
+    assert!(node.contains("_dep();"));
+
+    let none = run(InteropStrategy::None);
+    assert!(!none.contains("interopDefault"));
+    assert!(none.contains("(0, _dep.default)();"));
+  }
+
+  #[test]
+  fn test_injected_helper_ids() {
+    let make_asset = |source_type: SourceType| Asset {
+      code: Arc::new(Code::from(String::from(
+        "async function load() {\n  return await fetch(url);\n}",
+      ))),
+      env: Arc::new(Environment {
+        source_type,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let options = || ParcelOptions {
+      target_es_version: Some(EsVersion::Es2015),
+      ..ParcelOptions::default()
+    };
+
+    let result = run_test_with_options(make_asset(SourceType::Script), options()).unwrap();
+    assert!(result
+      .injected_helper_ids
+      .contains(&String::from("_async_to_generator")));
+
+    // Modules import the helpers instead of inlining them
+    let result = run_test_with_options(make_asset(SourceType::Module), options()).unwrap();
+    assert_eq!(result.injected_helper_ids, Vec::<String>::new());
+  }
+
+  #[test]
+  fn test_max_inline_helpers() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "async function load() {\n  return await fetch(url);\n}\nclass Foo {}\nvar [a, b] = list;",
+      ))),
+      env: Arc::new(Environment {
+        source_type: SourceType::Script,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let options = |max_inline_helpers| ParcelOptions {
+      max_inline_helpers,
+      target_es_version: Some(EsVersion::Es2015),
+      ..ParcelOptions::default()
+    };
+
+    let result = run_test_with_options(target_asset.clone(), options(None)).unwrap();
+    assert!(result.injected_helper_ids.len() > 1);
+    assert!(result.diagnostics.is_empty());
+
+    let result = run_test_with_options(target_asset, options(Some(1))).unwrap();
+    let code = std::str::from_utf8(result.asset.code.bytes()).unwrap();
+
+    assert_eq!(result.injected_helper_ids, Vec::<String>::new());
+    assert!(result
+      .dependencies
+      .iter()
+      .any(|dependency| dependency.specifier.starts_with("@swc/helpers/")));
+    assert!(!code.contains("import "));
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].severity, DiagnosticSeverity::Info);
+    assert!(result.diagnostics[0]
+      .message
+      .contains("imported from @swc/helpers instead"));
+  }
+
+  #[test]
+  fn test_trailing_newline() {
+    let run = |trailing_newline: TrailingNewline| {
+      let asset = Asset {
+        code: Arc::new(Code::from(String::from("function hello() {}"))),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      };
+      let options = ParcelOptions {
+        trailing_newline,
+        ..ParcelOptions::default()
+      };
+
+      run_test_with_options(asset, options)
+        .unwrap()
+        .asset
+        .code
+        .to_string()
+    };
+
+    assert_eq!(run(TrailingNewline::Preserve), "function hello() {}\n");
+    assert_eq!(run(TrailingNewline::Ensure), "function hello() {}\n");
+    assert_eq!(run(TrailingNewline::Strip), "function hello() {}");
+  }
+
+  #[test]
+  fn test_reserved_globals_are_not_renamed() {
+    let run = |reserved_globals: Vec<String>| {
+      let asset = Asset {
+        code: Arc::new(Code::from(String::from(
+          "var jQuery = window.jQuery || function () {};\njQuery.fn = {};\nexport const a = 1;",
+        ))),
+        env: Arc::new(Environment {
+          should_scope_hoist: true,
+          ..Environment::default()
+        }),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      };
+      let options = ParcelOptions {
+        reserved_globals,
+        ..ParcelOptions::default()
+      };
+
+      run_test_with_options(asset, options)
+        .unwrap()
+        .asset
+        .code
+        .to_string()
+    };
+
+    assert!(run(Vec::new()).contains("$var$jQuery"));
+
+    let code = run(vec![String::from("jQuery")]);
+    assert!(!code.contains("$var$jQuery"));
+    assert!(code.contains("var jQuery = window.jQuery"));
+    assert!(code.contains("jQuery.fn = {}"));
+  }
+
+  #[test]
+  fn test_ban_var() {
+    let run = |ban_var: bool| {
+      let asset = Asset {
+        code: Arc::new(Code::from(String::from(
+          "const a = 1;\nvar b = 2;\nlet c = 3;",
+        ))),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      };
+      let options = ParcelOptions {
+        ban_var,
+        ..ParcelOptions::default()
+      };
+
+      run_test_with_options(asset, options).unwrap().diagnostics
+    };
+
+    assert_eq!(run(false), vec![]);
+
+    let diagnostics = run(true);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Unexpected `var` declaration.");
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(
+      diagnostics[0].code_frames[0].code_highlights[0].start,
+      Location { line: 2, column: 1 }
+    );
+  }
+
+  #[test]
+  fn test_source_map_include_content() {
+    let run = |source_map_include_content: bool| {
+      let asset = Asset {
+        code: Arc::new(Code::from(String::from("export const a = 1;"))),
+        env: Arc::new(Environment {
+          source_map: Some(TargetSourceMapOptions::default()),
+          ..Environment::default()
+        }),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      };
+      let options = ParcelOptions {
+        source_map_include_content,
+        ..ParcelOptions::default()
+      };
+
+      run_test_with_options(asset, options)
+        .unwrap()
+        .asset
+        .map
+        .unwrap()
+    };
+
+    assert!(run(true).contains(r#""sourcesContent":["export const a = 1;"]"#));
+    assert!(!run(false).contains("sourcesContent"));
+  }
+
+  #[test]
+  fn test_max_source_map_bytes() {
+    let run = |max_source_map_bytes: usize| {
+      let asset = Asset {
+        code: Arc::new(Code::from(String::from("export const a = 1;"))),
+        env: Arc::new(Environment {
+          source_map: Some(TargetSourceMapOptions::default()),
+          ..Environment::default()
+        }),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      };
+      let options = ParcelOptions {
+        max_source_map_bytes: Some(max_source_map_bytes),
+        ..ParcelOptions::default()
+      };
+
+      run_test_with_options(asset, options).unwrap()
+    };
+
+    let result = run(usize::MAX);
+    assert!(result.asset.map.is_some());
+    assert!(result.diagnostics.is_empty());
+
+    let result = run(1);
+    assert_eq!(result.asset.map, None);
+    assert!(result.asset.code.to_string().contains("const a = 1;"));
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert!(result.diagnostics[0]
+      .message
+      .starts_with("The source map of this file is "));
+  }
+
+  #[test]
+  fn test_output_indent() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "function hello() {\n  if (a) {\n    b();\n  }\n}",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test_with_options(
+      target_asset,
+      ParcelOptions {
+        output_indent: 2,
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap();
+
+    assert_eq!(
+      result.asset.code.to_string(),
+      "function hello() {\n  if (a) {\n    b();\n  }\n}\n"
+    );
+  }
+
+  #[test]
+  fn test_unused_imports_are_reported() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "import { used, unused } from 'x';\nused();",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+
+    let result = run_test(target_asset.clone()).unwrap();
+    assert_eq!(result.diagnostics, vec![]);
+
+    let result = run_test_with_options(
+      target_asset,
+      ParcelOptions {
+        report_unused_imports: true,
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap();
+
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].severity, DiagnosticSeverity::Info);
+    assert_eq!(
+      result.diagnostics[0].message,
+      "`unused` is imported from 'x' but never used."
+    );
+  }
+
+  #[test]
+  fn test_css_module_imports_are_flagged() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "import styles from './button.module.css';\nimport './global.css';\nconsole.log(styles);",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test(target_asset).unwrap();
+    let is_css_module = |specifier: &str| {
+      result
+        .dependencies
+        .iter()
+        .find(|dependency| dependency.specifier == specifier)
+        .map(|dependency| dependency.is_css_module)
+    };
+
+    assert_eq!(is_css_module("./button.module.css"), Some(true));
+    assert_eq!(is_css_module("./global.css"), Some(false));
+  }
+
+  #[test]
+  fn test_dependency_priority() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        r#"
+        import './static';
+        import('./lazy');
+        new Worker(new URL('./worker', import.meta.url));
+      "#,
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test(target_asset).unwrap();
+    let priorities = result
+      .dependencies
+      .iter()
+      .map(|dependency| (dependency.specifier.as_str(), dependency.priority))
+      .collect::<Vec<_>>();
+
+    assert!(priorities.contains(&("./static", Priority::Sync)));
+    assert!(priorities.contains(&("./lazy", Priority::Lazy)));
+    assert!(priorities.contains(&("./worker", Priority::Parallel)));
+  }
+
+  #[test]
+  fn test_webpack_chunk_name_comment() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "import(/* webpackChunkName: \"foo\" */ './m');\nimport('./other');",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let result = run_test(target_asset).unwrap();
+    let chunk_name = |specifier: &str| {
+      result
+        .dependencies
+        .iter()
+        .find(|dependency| dependency.specifier == specifier)
+        .and_then(|dependency| dependency.chunk_name.clone())
+    };
+
+    assert_eq!(chunk_name("./m"), Some(String::from("foo")));
+    assert_eq!(chunk_name("./other"), None);
+  }
+
+  #[test]
+  fn test_preserve_jsx() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "import React from 'react';\nexport const App = () => <div/>;",
+      ))),
+      file_path: "mock_path.jsx".into(),
+      ..Asset::default()
+    };
+
+    let result = run_test_with_options(
+      target_asset.clone(),
+      ParcelOptions {
+        preserve_jsx: true,
+        ..ParcelOptions::default()
+      },
+    )
+    .unwrap();
+    let code = result.asset.code.to_string();
+
+    assert!(code.contains("<div/>"));
+    assert!(!code.contains("createElement"));
+    assert!(result
+      .dependencies
+      .iter()
+      .any(|dependency| dependency.specifier == "react"));
+
+    let code = run_test(target_asset).unwrap().asset.code.to_string();
+
+    assert!(!code.contains("<div/>"));
+    assert!(code.contains("createElement"));
+  }
+
+  #[test]
+  fn test_relative_file_paths() {
+    let run = |relative_file_paths: bool| {
+      let options = Arc::new(ParcelOptions {
+        relative_file_paths,
+        ..ParcelOptions::default()
+      });
+      let mut context = RunTransformContext::new(
+        Arc::new(InMemoryFileSystem::default()),
+        options,
+        PathBuf::from("/project"),
+      );
+      let input = TransformationInput::Asset(Asset {
+        code: Arc::new(Code::from(String::from(
+          "import './dep';\nconsole.log(__filename);",
+        ))),
+        file_path: "/project/src/index.js".into(),
+        ..Asset::default()
+      });
+
+      let result = ParcelJsTransformerPlugin::new()
+        .transform(&mut context, input)
+        .unwrap();
+      let dependency = result
+        .dependencies
+        .iter()
+        .find(|dependency| dependency.specifier == "./dep")
+        .unwrap()
+        .clone();
+
+      (
+        result.asset.code.to_string(),
+        dependency.source_path,
+        dependency.resolve_from,
+      )
+    };
+
+    // __filename is derived from the filename passed to SWC. An absolute one is made relative to
+    // the project root, while a relative one falls back to the file name.
+    let (code, source_path, resolve_from) = run(false);
+    assert!(code.contains("\"src/index.js\""));
+    assert_eq!(source_path, Some(PathBuf::from("/project/src/index.js")));
+    assert_eq!(resolve_from, None);
+
+    let (code, source_path, resolve_from) = run(true);
+    assert!(!code.contains("\"src/index.js\""));
+    assert!(code.contains("\"/index.js\""));
+    assert_eq!(source_path, Some(PathBuf::from("src/index.js")));
+    assert_eq!(resolve_from, Some(PathBuf::from("/project/src/index.js")));
+  }
+
+  #[test]
+  fn test_relative_source_paths_keep_the_absolute_filename() {
+    let options = Arc::new(ParcelOptions {
+      relative_source_paths: true,
+      ..ParcelOptions::default()
+    });
+    let mut context = RunTransformContext::new(
+      Arc::new(InMemoryFileSystem::default()),
+      options,
+      PathBuf::from("/project"),
+    );
+    let input = TransformationInput::Asset(Asset {
+      code: Arc::new(Code::from(String::from(
+        "import './dep';\nconsole.log(__filename);",
+      ))),
+      file_path: "/project/src/index.js".into(),
+      ..Asset::default()
+    });
+
+    let result = ParcelJsTransformerPlugin::new()
+      .transform(&mut context, input)
+      .unwrap();
+    let dependency = result
+      .dependencies
+      .iter()
+      .find(|dependency| dependency.specifier == "./dep")
+      .unwrap();
+
+    // SWC still gets the absolute filename, which __filename is derived from
+    assert!(result.asset.code.to_string().contains("\"src/index.js\""));
+    assert_eq!(dependency.source_path, Some(PathBuf::from("src/index.js")));
+    assert_eq!(
+      dependency.resolve_from,
+      Some(PathBuf::from("/project/src/index.js"))
+    );
+  }
+
+  #[test]
+  fn test_code_postprocessor() {
+    let mut context = RunTransformContext::new(
+      Arc::new(InMemoryFileSystem::default()),
+      Arc::new(ParcelOptions::default()),
+      PathBuf::default(),
+    )
+    .with_code_postprocessor(Arc::new(|code: String| {
+      format!("(function () {{\n{}}})();\n", code)
+    }));
+    let input = TransformationInput::Asset(Asset {
+      code: Arc::new(Code::from(String::from("const x = 1;"))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    });
+
+    let result = ParcelJsTransformerPlugin::new()
+      .transform(&mut context, input)
+      .unwrap();
+
+    assert_eq!(
+      result.asset.code.to_string(),
+      "(function () {\nconst x = 1;\n})();\n"
+    );
+  }
+
+  #[test]
+  fn test_code_changed() {
+    let run = |code: &str| {
+      let original = Code::from(String::from(code));
+      let result = run_test(Asset {
+        code: Arc::new(original.clone()),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      })
+      .unwrap();
+
+      result.code_changed(&original)
+    };
+
+    assert!(!run("function hello() {}"));
+    assert!(run("const x = require('other');"));
+  }
+
+  #[test]
+  fn test_unawaited_dynamic_imports_are_preloaded() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "async function run() {\n  await import('./awaited');\n  const p = import('./stored');\n}",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let preload = |options: ParcelOptions, specifier: &str| {
+      run_test_with_options(target_asset.clone(), options)
+        .unwrap()
+        .dependencies
+        .into_iter()
+        .find(|dependency| dependency.specifier == specifier)
+        .map(|dependency| dependency.preload)
+    };
+    let options = || ParcelOptions {
+      preload_unawaited_imports: true,
+      ..ParcelOptions::default()
+    };
+
+    assert_eq!(preload(options(), "./awaited"), Some(false));
+    assert_eq!(preload(options(), "./stored"), Some(true));
+    assert_eq!(preload(ParcelOptions::default(), "./stored"), Some(false));
+  }
+
+  #[test]
+  fn test_transform_semaphore_is_released_after_transforming() {
+    let semaphore = Arc::new(Semaphore::new(1));
+    let mut context = RunTransformContext::new(
+      Arc::new(InMemoryFileSystem::default()),
+      Arc::new(ParcelOptions::default()),
+      PathBuf::default(),
+    )
+    .with_transform_semaphore(semaphore.clone());
+
+    for _ in 0..2 {
+      let input = TransformationInput::Asset(Asset {
+        code: Arc::new(Code::from(String::from("export const a = 1;"))),
+        file_path: "index.js".into(),
+        ..Asset::default()
+      });
+
+      assert!(ParcelJsTransformerPlugin::new()
+        .transform(&mut context, input)
+        .is_ok());
+    }
+
+    // Blocks forever if the transforms did not release their permit
+    drop(semaphore.acquire());
+  }
+
+  #[test]
+  fn test_esm_is_lowered_to_commonjs_output() {
+    let result = run_test(Asset {
+      code: Arc::new(Code::from(String::from(
+        "import b from './b';\nexport { c } from './c';\nexport const a = b;",
+      ))),
+      env: Arc::new(Environment {
+        output_format: OutputFormat::CommonJS,
+        should_scope_hoist: false,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    })
+    .unwrap();
+
+    let code = result.asset.code.to_string();
+    // ES module dependencies keep their specifier, rather than being given a placeholder
+    let placeholder = |specifier: &str| {
+      let dependency = result
+        .dependencies
+        .iter()
+        .find(|dependency| dependency.specifier == specifier)
+        .unwrap();
+
+      dependency
+        .placeholder
+        .clone()
+        .unwrap_or_else(|| dependency.specifier.clone())
+    };
+
+    assert!(!code.contains("import "));
+    assert!(!code.contains("export "));
+    assert!(code.contains(&format!("require(\"{}\")", placeholder("./b"))));
+    assert!(code.contains(&format!("require(\"{}\")", placeholder("./c"))));
+    assert!(code.contains("(exports, \"a\""));
+    assert!(code.contains("(exports, \"c\""));
+  }
+
+  #[test]
+  fn test_cjs_output_directive() {
+    // Only libraries keep `new URL(..., import.meta.url)`, other URLs are resolved at runtime
+    let make_asset = |code: &str| Asset {
+      code: Arc::new(Code::from(String::from(code))),
+      env: Arc::new(Environment {
+        is_library: true,
+        output_format: OutputFormat::EsModule,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+
+    let result = run_test(make_asset(
+      "const url = new URL('./image.png', import.meta.url);",
+    ))
+    .unwrap();
+
+    assert_eq!(result.asset.env.output_format, OutputFormat::EsModule);
+    assert!(result.asset.code.to_string().contains("import.meta.url"));
+    assert_eq!(result.diagnostics, vec![]);
+
+    let result = run_test(make_asset(
+      "// Loaded through require\n'use cjs-output';\nconst url = new URL('./image.png', import.meta.url);",
+    ))
+    .unwrap();
+
+    assert_eq!(result.asset.env.output_format, OutputFormat::CommonJS);
+    assert!(result
+      .asset
+      .code
+      .to_string()
+      .contains("\"file:\" + __filename"));
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].severity, DiagnosticSeverity::Info);
+  }
+
+  #[test]
+  fn test_has_directive() {
+    assert!(has_directive("'use cjs-output';", "use cjs-output"));
+    assert!(has_directive(
+      "#!/usr/bin/env node\n/* header */\n\"use strict\";\n\"use cjs-output\"\nexport {};",
+      "use cjs-output"
+    ));
+    assert!(!has_directive(
+      "const a = 'use cjs-output';",
+      "use cjs-output"
+    ));
+    assert!(!has_directive("'use strict';", "use cjs-output"));
+  }
+
+  #[test]
+  fn test_require_is_kept_for_commonjs_output_without_scope_hoisting() {
+    let result = run_test(Asset {
+      code: Arc::new(Code::from(String::from(
+        "const other = require('other');\nmodule.exports = other;",
+      ))),
+      env: Arc::new(Environment {
+        output_format: OutputFormat::CommonJS,
+        should_scope_hoist: false,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    })
+    .unwrap();
+
+    assert_eq!(result.dependencies.len(), 1);
+
+    let dependency = &result.dependencies[0];
+    assert_eq!(dependency.specifier, "other");
+    assert_eq!(dependency.specifier_type, SpecifierType::CommonJS);
+
+    let placeholder = dependency
+      .meta
+      .get("placeholder")
+      .and_then(|placeholder| placeholder.as_str())
+      .unwrap();
+
+    let code = result.asset.code.to_string();
+    assert!(code.contains(&format!("require(\"{}\")", placeholder)));
+    assert!(!code.contains("require('other')"));
+  }
+
+  #[test]
+  fn test_exported_calls_are_annotated_as_pure() {
+    let make_asset = || Asset {
+      code: Arc::new(Code::from(String::from(
+        "export const x = makeThing();\nexport const y = /*#__PURE__*/ makeOther();\nrun();",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+
+    let code = run_test(make_asset()).unwrap().asset.code.to_string();
+    assert!(!code.contains("/*#__PURE__*/ makeThing()"));
+
+    let options = ParcelOptions {
+      annotate_pure: true,
+      ..ParcelOptions::default()
+    };
+
+    let code = run_test_with_options(make_asset(), options)
+      .unwrap()
+      .asset
+      .code
+      .to_string();
+
+    assert!(code.contains("/*#__PURE__*/ makeThing()"));
+    assert_eq!(code.matches("/*#__PURE__*/ makeOther()").count(), 1);
+    assert!(!code.contains("/*#__PURE__*/ run()"));
+  }
+
+  #[test]
+  fn test_dependencies_carry_the_source_asset_type() {
+    let result = run_test(Asset {
+      asset_type: FileType::Tsx,
+      code: Arc::new(Code::from(String::from(
+        "import a from './a';\nconst b = require('./b');\nimport('./c');\nexport { a, b };",
+      ))),
+      file_path: "mock_path.tsx".into(),
+      ..Asset::default()
+    })
+    .unwrap();
+
+    assert!(!result.dependencies.is_empty());
+    assert!(result
+      .dependencies
+      .iter()
+      .all(|dependency| dependency.source_asset_type == Some(FileType::Tsx)));
+  }
+
+  #[test]
+  fn test_require_explicit_extensions() {
+    let run = |code: &str| {
+      let asset = Asset {
+        code: Arc::new(Code::from(String::from(code))),
+        env: Arc::new(Environment {
+          source_type: SourceType::Module,
+          ..Environment::default()
+        }),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      };
+      let options = ParcelOptions {
+        require_explicit_extensions: true,
+        ..ParcelOptions::default()
+      };
+
+      run_test_with_options(asset, options).unwrap().diagnostics
+    };
+
+    let diagnostics = run("import './x';");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(
+      diagnostics[0].message,
+      "The import of './x' is missing a file extension."
+    );
+
+    assert_eq!(run("import './x.js';"), vec![]);
+    assert_eq!(run("import 'react';"), vec![]);
+  }
+
+  #[test]
+  fn test_missing_extension_suggestion() {
+    let asset = Asset {
+      code: Arc::new(Code::from(String::from("import './x';"))),
+      env: Arc::new(Environment {
+        source_type: SourceType::Module,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let options = ParcelOptions {
+      require_explicit_extensions: true,
+      ..ParcelOptions::default()
+    };
+
+    let result = run_test_with_options(asset, options).unwrap();
+
+    assert_eq!(
+      result.diagnostics[0].suggestions,
+      vec![CodeSuggestion {
+        loc: SourceLocation {
+          file_path: PathBuf::from("mock_path.js"),
+          start: Location { line: 1, column: 8 },
+          end: Location {
+            line: 1,
+            column: 13
+          },
+        },
+        replacement: String::from("'./x.js'"),
+      }]
+    );
+  }
+
+  #[test]
+  fn test_warn_relative_depth() {
+    let run = |code: &str| {
+      let asset = Asset {
+        code: Arc::new(Code::from(String::from(code))),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      };
+      let options = ParcelOptions {
+        warn_relative_depth: Some(3),
+        ..ParcelOptions::default()
+      };
+
+      run_test_with_options(asset, options).unwrap().diagnostics
+    };
+
+    let diagnostics = run("import '../../../../x.js';");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(
+      diagnostics[0].message,
+      "The import of '../../../../x.js' goes up 4 directories, which is more than the limit of 3."
+    );
+
+    assert_eq!(run("import '../../../x.js';"), vec![]);
+    assert_eq!(run("import 'react';"), vec![]);
+  }
+
+  #[test]
+  fn test_too_many_dependencies_warning() {
+    let make_asset = || Asset {
+      code: Arc::new(Code::from(
+        (0..5)
+          .map(|i| format!("export * from './module-{}';\n", i))
+          .collect::<String>(),
+      )),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let run = |warn_dependency_count: Option<usize>| {
+      run_test_with_options(
+        make_asset(),
+        ParcelOptions {
+          warn_dependency_count,
+          ..ParcelOptions::default()
+        },
+      )
+      .unwrap()
+      .diagnostics
+    };
+
+    assert!(run(None).is_empty());
+    assert!(run(Some(5)).is_empty());
+
+    let diagnostics = run(Some(3));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(
+      diagnostics[0].message,
+      "This file has 5 dependencies, which is more than the limit of 3."
+    );
+  }
+
+  #[test]
+  fn test_inline_module_script() {
+    let run = |parent_path: &str, index: usize| {
+      let mut context = RunTransformContext::new(
+        Arc::new(InMemoryFileSystem::default()),
+        Arc::new(ParcelOptions::default()),
+        PathBuf::default(),
+      );
+      let input = TransformationInput::InlineScript(InlineScript::new(
+        PathBuf::from(parent_path),
+        index,
+        String::from("import './dep';\nexport const a = 1;"),
+        Arc::new(Environment::default()),
+        SourceType::Module,
+      ));
+
+      ParcelJsTransformerPlugin::new()
+        .transform(&mut context, input)
+        .unwrap()
+    };
+
+    let result = run("/project/index.html", 0);
+
+    assert_eq!(
+      result.asset.file_path,
+      PathBuf::from("/project/index.html.0.js")
+    );
+    assert_eq!(result.asset.env.source_type, SourceType::Module);
+    assert_eq!(result.dependencies[0].specifier, "./dep");
+    assert!(result.asset.unique_key.is_some());
+    assert_eq!(
+      result.asset.unique_key,
+      run("/project/index.html", 0).asset.unique_key
+    );
+    assert_ne!(
+      result.asset.unique_key,
+      run("/project/index.html", 1).asset.unique_key
+    );
+    assert_ne!(
+      result.asset.unique_key,
+      run("/project/other.html", 0).asset.unique_key
+    );
+  }
+
+  #[test]
+  fn test_fold_environment_checks() {
+    let specifiers = |context: EnvironmentContext| {
+      let target_asset = Asset {
+        code: Arc::new(Code::from(String::from(
+          "if (typeof window !== 'undefined') {\n  require('./browser');\n} else {\n  require('./node');\n}",
+        ))),
+        env: Arc::new(Environment {
+          context,
+          ..Environment::default()
+        }),
+        file_path: "mock_path.js".into(),
+        ..Asset::default()
+      };
+      let options = ParcelOptions {
+        fold_environment_checks: true,
+        ..ParcelOptions::default()
+      };
+
+      run_test_with_options(target_asset, options)
+        .unwrap()
+        .dependencies
+        .into_iter()
+        .map(|dependency| dependency.specifier)
+        .collect::<Vec<String>>()
+    };
+
+    assert_eq!(specifiers(EnvironmentContext::Browser), vec!["./browser"]);
+    assert_eq!(specifiers(EnvironmentContext::Node), vec!["./node"]);
+    assert_eq!(specifiers(EnvironmentContext::WebWorker), vec!["./node"]);
+  }
+
+  #[test]
+  fn test_node_env_is_replaced_with_build_mode() {
+    let target_asset = Asset {
+      code: Arc::new(Code::from(String::from(
+        "if (process.env.NODE_ENV === 'production') {\n  require('./prod');\n} else {\n  require('./dev');\n}",
+      ))),
+      env: Arc::new(Environment {
+        context: EnvironmentContext::Node,
+        ..Environment::default()
+      }),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    };
+    let specifiers = |options: ParcelOptions| {
+      run_test_with_options(target_asset.clone(), options)
+        .unwrap()
+        .dependencies
+        .into_iter()
+        .map(|dependency| dependency.specifier)
+        .collect::<Vec<String>>()
+    };
+
+    assert_eq!(
+      specifiers(ParcelOptions {
+        mode: BuildMode::Development,
+        ..ParcelOptions::default()
+      }),
+      vec![String::from("./dev")]
+    );
+    assert_eq!(
+      specifiers(ParcelOptions {
+        mode: BuildMode::Production,
+        ..ParcelOptions::default()
+      }),
+      vec![String::from("./prod")]
+    );
+    assert_eq!(
+      specifiers(ParcelOptions {
+        env: Some(HashMap::from([(
+          String::from("NODE_ENV"),
+          String::from("production")
+        )])),
+        mode: BuildMode::Development,
+        ..ParcelOptions::default()
+      }),
+      vec![String::from("./prod")]
+    );
+  }
+
+  #[test]
+  fn test_shebang_is_kept_for_entries() {
+    let run = |is_entry: bool| {
+      run_test_with_input(
+        TransformationInput::InitialAsset(InitialAsset {
+          code: Some(String::from("#!/usr/bin/env node\nconsole.log('hello');")),
+          file_path: PathBuf::from("bin.js"),
+          is_entry,
+          ..InitialAsset::default()
+        }),
+        ParcelOptions::default(),
+      )
+      .unwrap()
+    };
+
+    let entry = run(true);
+    assert_eq!(
+      entry
+        .asset
+        .meta
+        .get("interpreter")
+        .and_then(|interpreter| interpreter.as_str()),
+      Some("/usr/bin/env node")
+    );
+    assert!(!entry.asset.code.to_string().contains("#!"));
+
+    let non_entry = run(false);
+    assert_eq!(non_entry.asset.meta.get("interpreter"), None);
+    assert!(!non_entry.asset.code.to_string().contains("#!"));
+  }
+
+  #[test]
+  fn test_undefined_env_in_browser_builds() {
+    let result = run_test(Asset {
+      code: Arc::new(Code::from(String::from(
+        "const a = 1;\nconsole.log(process.env.UNKNOWN);",
+      ))),
+      file_path: "mock_path.js".into(),
+      ..Asset::default()
+    })
+    .unwrap();
+
+    assert_eq!(result.diagnostics.len(), 1);
+    assert_eq!(result.diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(
+      result.diagnostics[0].message,
+      "process.env.UNKNOWN is not defined, so it is replaced with undefined"
+    );
+
+    let code_highlight = &result.diagnostics[0].code_frames[0].code_highlights[0];
+    assert_eq!(code_highlight.start.line, 2);
+    assert_eq!(code_highlight.start.column, 13);
+  }
+
+  #[test]
+  fn test_scope_hoisting_a_script_environment_is_an_error() {
+    let error = run_test(Asset {
+      code: Arc::new(Code::from(String::from("const a = 1;"))),
+      env: Arc::new(Environment {
+        should_scope_hoist: true,
+        source_type: SourceType::Script,
+        ..Environment::default()
+      }),
+      file_path: "index.js".into(),
+      ..Asset::default()
+    })
+    .unwrap_err();
+
+    let diagnostic = error.downcast_ref::<Diagnostic>().unwrap();
+    assert_eq!(
+      diagnostic.message,
+      "Invalid JavaScript transformer environment for index.js."
+    );
+    assert_eq!(
+      diagnostic.hints,
+      vec![String::from(
+        "`should_scope_hoist` cannot be enabled when `source_type` is `Script`"
+      )]
+    );
+  }
+
+  #[test]
+  fn test_scope_hoisting_a_cjs_file_is_not_an_error() {
+    let result = run_test(Asset {
+      code: Arc::new(Code::from(String::from("module.exports = 1;"))),
+      env: Arc::new(Environment {
+        should_scope_hoist: true,
+        ..Environment::default()
+      }),
+      file_path: "index.cjs".into(),
+      ..Asset::default()
+    });
+
+    assert!(result.is_ok());
+  }
+
+  fn run_test(asset: Asset) -> anyhow::Result<TransformResult> {
+    run_test_with_options(asset, ParcelOptions::default())
+  }
+
+  fn run_test_with_options(
+    asset: Asset,
+    options: ParcelOptions,
+  ) -> anyhow::Result<TransformResult> {
+    run_test_with_input(TransformationInput::Asset(asset), options)
+  }
+
+  fn run_test_with_input(
+    input: TransformationInput,
+    options: ParcelOptions,
+  ) -> anyhow::Result<TransformResult> {
+    let file_system = Arc::new(InMemoryFileSystem::default());
+    let options = Arc::new(options);
     let mut context = RunTransformContext::new(file_system, options, PathBuf::default());
     let mut transformer = ParcelJsTransformerPlugin::new();
-    let input = TransformationInput::Asset(asset);
 
     let result = transformer.transform(&mut context, input)?;
     Ok(result)
@@ -1,19 +1,22 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use indexmap::IndexMap;
 use parcel_core::diagnostic;
+use sourcemap::{RawToken, SourceMap};
 use swc_core::atoms::Atom;
 
 use parcel_core::plugin::TransformResult;
 use parcel_core::types::engines::EnvironmentFeature;
 use parcel_core::types::{
-  Asset, BundleBehavior, Code, CodeFrame, CodeHighlight, Dependency, Diagnostic, DiagnosticBuilder,
-  Environment, EnvironmentContext, File, FileType, IncludeNodeModules, OutputFormat, ParcelOptions,
-  SourceLocation, SourceType, SpecifierType, Symbol,
+  Asset, BundleBehavior, Code, CodeHighlight, CodeSuggestion, Dependency, Diagnostic,
+  DiagnosticBuilder, DiagnosticSeverity, Environment, EnvironmentContext, FileType,
+  IncludeNodeModules, OutputFormat, ParcelOptions, Priority, SourceLocation, SourceType,
+  SpecifierType, Symbol, TrailingNewline,
 };
 
 use crate::transformer::conversion::dependency_kind::{convert_priority, convert_specifier_type};
+use crate::transformer::conversion::diagnostics::make_code_frame;
 use crate::transformer::conversion::loc::convert_loc;
 use crate::transformer::conversion::symbol::{
   transformer_collect_imported_symbol_to_symbol, transformer_exported_symbol_into_symbol,
@@ -21,6 +24,9 @@ use crate::transformer::conversion::symbol::{
 };
 
 mod dependency_kind;
+/// Conversions from SWC diagnostics into [`Diagnostic`]
+mod diagnostics;
+pub(crate) use diagnostics::{convert_diagnostic, convert_error};
 mod loc;
 /// Conversions from SWC symbol types into [`Symbol`]
 mod symbol;
@@ -30,8 +36,18 @@ pub(crate) fn convert_result(
   transformer_config: &parcel_js_swc_core::Config,
   result: parcel_js_swc_core::TransformResult,
   options: &ParcelOptions,
+  project_root: &Path,
+  report_diagnostic: &dyn Fn(&Diagnostic),
 ) -> Result<TransformResult, Vec<Diagnostic>> {
   let asset_file_path = asset.file_path.to_path_buf();
+  let source_path = if options.relative_file_paths || options.relative_source_paths {
+    asset_file_path
+      .strip_prefix(project_root)
+      .unwrap_or(&asset_file_path)
+      .to_path_buf()
+  } else {
+    asset_file_path.clone()
+  };
   let asset_environment = asset.env.clone();
   let asset_id = asset.id();
 
@@ -39,14 +55,79 @@ pub(crate) fn convert_result(
     asset.set_interpreter(shebang);
   }
 
-  let (mut dependency_by_specifier, invalidate_on_file_change) =
-    convert_dependencies(transformer_config, result.dependencies, &asset, asset_id)?;
+  // Each diagnostic is reported as soon as it is found, so that it is not lost if a later error
+  // fails the transformation
+  let mut diagnostics: Vec<Diagnostic> = Vec::new();
+  let mut add_diagnostic = |diagnostic: Diagnostic| {
+    report_diagnostic(&diagnostic);
+    diagnostics.push(diagnostic);
+  };
+
+  // Any errors have already been handled by the transformer, so only warnings are left here
+  for diagnostic in result.diagnostics.unwrap_or_default() {
+    add_diagnostic(convert_diagnostic(&asset, diagnostic));
+  }
+
+  let (mut dependency_by_specifier, invalidate_on_file_change) = convert_dependencies(
+    transformer_config,
+    result.dependencies,
+    &asset,
+    asset_id,
+    options,
+    &source_path,
+  )?;
+
+  if options.assume_esm {
+    for dependency in dependency_by_specifier.values() {
+      if dependency.specifier_type == SpecifierType::CommonJS {
+        add_diagnostic(make_unexpected_require_diagnostic(&asset, dependency));
+      }
+    }
+  }
+
+  for import in &result.unused_imports {
+    if is_redundant_react_import(&asset, options, import) {
+      add_diagnostic(make_redundant_react_import_diagnostic(&asset, import));
+    } else if options.report_unused_imports {
+      add_diagnostic(make_unused_import_diagnostic(&asset, import));
+    }
+  }
+
+  if options.require_explicit_extensions && asset.env.source_type == SourceType::Module {
+    for dependency in dependency_by_specifier.values() {
+      if dependency.specifier_type == SpecifierType::Esm && is_missing_extension(dependency) {
+        add_diagnostic(make_missing_extension_diagnostic(&asset, dependency));
+      }
+    }
+  }
+
+  if let Some(limit) = options.warn_relative_depth {
+    for dependency in dependency_by_specifier.values() {
+      let depth = relative_depth(&dependency.specifier);
+      if depth > limit {
+        add_diagnostic(make_relative_depth_diagnostic(
+          &asset, dependency, depth, limit,
+        ));
+      }
+    }
+  }
+
+  if let Some(limit) = options.warn_dependency_count {
+    if dependency_by_specifier.len() > limit {
+      add_diagnostic(make_dependency_count_diagnostic(
+        &asset,
+        dependency_by_specifier.len(),
+        limit,
+      ));
+    }
+  }
 
   if result.needs_esm_helpers {
     let has_symbols = result.hoist_result.is_some() || result.symbol_result.is_some();
     let dependency = make_esm_helpers_dependency(
       options,
-      &asset_file_path,
+      &source_path,
+      asset.asset_type.clone(),
       (*asset_environment).clone(),
       has_symbols,
       asset_id,
@@ -101,16 +182,25 @@ pub(crate) fn convert_result(
           let re_export_fake_local_key = existing
             .map(|sym| sym.local.clone())
             .unwrap_or_else(|| format!("${:016x}$re_export${}", asset_id, symbol.local).into());
-          let symbol = Symbol {
+          let loc = Some(convert_loc(asset_file_path.clone(), &symbol.loc));
+          let is_weak = existing.map(|e| e.is_weak).unwrap_or(true);
+
+          // The dependency symbol is the imported name (`*` for `export * as ns`), while the asset
+          // exports it under its rename
+          dependency.symbols.push(Symbol {
             exported: symbol.imported.as_ref().into(),
             local: re_export_fake_local_key.clone(),
-            loc: Some(convert_loc(asset_file_path.clone(), &symbol.loc)),
-            is_weak: existing.map(|e| e.is_weak).unwrap_or(true),
+            loc: loc.clone(),
+            is_weak,
             ..Symbol::default()
-          };
-
-          dependency.symbols.push(symbol.clone());
-          asset.symbols.push(symbol);
+          });
+          asset.symbols.push(Symbol {
+            exported: symbol.local.as_ref().into(),
+            local: re_export_fake_local_key,
+            loc,
+            is_weak,
+            ..Symbol::default()
+          });
         }
       }
     }
@@ -162,6 +252,8 @@ pub(crate) fn convert_result(
       asset.symbols.push(make_export_star_symbol(asset_id));
     }
 
+    mark_side_effect_only_imports(&mut dependency_by_specifier);
+
     asset.set_has_cjs_exports(hoist_result.has_cjs_exports);
     asset.set_static_exports(hoist_result.static_cjs_exports);
     asset.set_should_wrap(hoist_result.should_wrap);
@@ -188,10 +280,13 @@ pub(crate) fn convert_result(
           (format!("${}", sym.local).into(), false)
         };
 
+        // The `*` symbol added for CommonJS exports has a placeholder location, as it does not
+        // correspond to any statement
+        let is_cjs_fallback = sym.exported == "*" && sym.source.is_none();
         asset.symbols.push(Symbol {
           exported: sym.exported.as_ref().into(),
           local,
-          loc: Some(convert_loc(asset_file_path.clone(), &sym.loc)),
+          loc: (!is_cjs_fallback).then(|| convert_loc(asset_file_path.clone(), &sym.loc)),
           is_weak,
           ..Symbol::default()
         });
@@ -232,10 +327,12 @@ pub(crate) fn convert_result(
       asset.symbols.push(make_export_star_symbol(asset_id));
     }
 
+    mark_side_effect_only_imports(&mut dependency_by_specifier);
+
     // For all other imports and requires, mark everything as imported (this covers both dynamic
     // imports and non-top-level requires.)
     for dep in dependency_by_specifier.values_mut() {
-      if dep.symbols.is_empty() {
+      if dep.symbols.is_empty() && !dep.side_effect_only {
         dep.symbols.push(Symbol {
           exported: "*".into(),
           local: format!("${}$", dep.specifier), // TODO: coalesce with dep.placeholder
@@ -246,11 +343,38 @@ pub(crate) fn convert_result(
     }
   }
 
+  if let Some(duplicate) = result.duplicate_exports.first() {
+    return Err(vec![make_duplicate_export_diagnostic(&asset, duplicate)]);
+  }
+
+  for dependency in dependency_by_specifier.values() {
+    if dependency.specifier_type == SpecifierType::Esm && is_likely_commonjs(dependency) {
+      for symbol in &dependency.symbols {
+        if symbol.exported == "default" {
+          add_diagnostic(make_commonjs_default_import_diagnostic(
+            &asset, dependency, symbol,
+          ));
+        }
+      }
+    }
+  }
+
+  if options.deterministic_symbol_order {
+    sort_symbols(&mut asset.symbols);
+    for dependency in dependency_by_specifier.values_mut() {
+      sort_symbols(&mut dependency.symbols);
+    }
+  }
+
   asset.set_has_node_replacements(result.has_node_replacements);
   asset.set_is_constant_module(result.is_constant_module);
 
   if asset.unique_key.is_none() {
-    asset.unique_key = Some(format!("{:016x}", asset_id));
+    asset.unique_key = Some(make_unique_key(
+      &asset_environment,
+      asset_id,
+      options.name_delimiter,
+    ));
   }
   asset.asset_type = FileType::Js;
 
@@ -259,11 +383,55 @@ pub(crate) fn convert_result(
     // TODO: This is impossible; but we should extend 'diagnostic' type to be nicer / easier to build
     .map_err(|_| vec![])?;
   asset.code = Arc::new(Code::from(result_source_code_string));
+  asset.map = result.map;
+
+  if let Some(banner) = &options.banner {
+    prepend_banner(&mut asset, banner);
+  }
+
+  // This goes above the banner, so that each asset in the output starts with its id
+  if options.emit_module_id_comment {
+    let module_id = asset
+      .unique_key
+      .clone()
+      .unwrap_or_else(|| format!("{:016x}", asset_id));
+    let comment = format!("/* module: {} */", module_id.replace("*/", "*\\/"));
+
+    prepend_banner(&mut asset, &comment);
+  }
+
+  if let Some(limit) = options.max_source_map_bytes {
+    let size = asset.map.as_ref().map_or(0, |map| map.len());
+    if size > limit {
+      asset.map = None;
+      add_diagnostic(make_source_map_size_diagnostic(&asset, size, limit));
+    }
+  }
+
+  if options.trailing_newline != TrailingNewline::Preserve {
+    let code = options.trailing_newline.apply(&asset.code.to_string());
+    asset.code = Arc::new(Code::from(code));
+  }
 
   Ok(TransformResult {
     asset,
     dependencies: dependency_by_specifier.into_values().collect(),
-    // map: result.map,
+    diagnostics,
+    extracted_css: result
+      .extracted_css
+      .iter()
+      .map(|(css, loc)| (css.clone(), convert_loc(asset_file_path.clone(), loc)))
+      .collect(),
+    i18n_keys: result
+      .i18n_keys
+      .iter()
+      .map(|(key, loc)| (key.clone(), convert_loc(asset_file_path.clone(), loc)))
+      .collect(),
+    injected_helper_ids: result
+      .injected_helpers
+      .iter()
+      .map(|name| name.to_string())
+      .collect(),
     // shebang: result.shebang,
     // dependencies: deps,
     // diagnostics: result.diagnostics,
@@ -295,6 +463,8 @@ pub(crate) fn convert_dependencies(
   dependencies: Vec<parcel_js_swc_core::DependencyDescriptor>,
   asset: &Asset,
   asset_id: u64,
+  options: &ParcelOptions,
+  source_path: &Path,
 ) -> Result<(IndexMap<Atom, Dependency>, Vec<PathBuf>), Vec<Diagnostic>> {
   let mut dependency_by_specifier = IndexMap::new();
   let mut invalidate_on_file_change = Vec::new();
@@ -305,11 +475,22 @@ pub(crate) fn convert_dependencies(
       .map(|d| d.as_str().into())
       .unwrap_or_else(|| transformer_dependency.specifier.clone());
 
-    let result = convert_dependency(transformer_config, &asset, asset_id, transformer_dependency)?;
+    let result = convert_dependency(
+      transformer_config,
+      &asset,
+      asset_id,
+      options,
+      source_path,
+      transformer_dependency,
+    )?;
 
     match result {
+      // Separate imports of the same specifier are coalesced into the first dependency, which
+      // the symbols of all of the imports are added to
       DependencyConversionResult::Dependency(dependency) => {
-        dependency_by_specifier.insert(placeholder, dependency);
+        dependency_by_specifier
+          .entry(placeholder)
+          .or_insert(dependency);
       }
       DependencyConversionResult::InvalidateOnFileChange(file_path) => {
         invalidate_on_file_change.push(file_path);
@@ -319,6 +500,364 @@ pub(crate) fn convert_dependencies(
   Ok((dependency_by_specifier, invalidate_on_file_change))
 }
 
+/// Flag static imports that import no symbols, such as `import 'polyfill'`
+///
+/// These are only imported for their side effects, so they must be kept even though none of their
+/// exports are used.
+fn mark_side_effect_only_imports(dependency_by_specifier: &mut IndexMap<Atom, Dependency>) {
+  for dependency in dependency_by_specifier.values_mut() {
+    dependency.side_effect_only =
+      dependency.is_esm && dependency.priority == Priority::Sync && dependency.symbols.is_empty();
+  }
+}
+
+/// When all files are assumed to be ES modules, `require` calls are likely to be a mistake
+fn make_unexpected_require_diagnostic(asset: &Asset, dependency: &Dependency) -> Diagnostic {
+  let code_highlights = dependency
+    .loc
+    .clone()
+    .map(|loc| vec![CodeHighlight::from(loc)])
+    .unwrap_or_default();
+
+  diagnostic!(DiagnosticBuilder::default()
+    .code_frames(vec![make_code_frame(asset, code_highlights)])
+    .hints(vec![String::from(
+      "Use a static `import`, or dynamic `import()` instead."
+    )])
+    .message(format!(
+      "Unexpected `require('{}')` in a file that is assumed to be an ES module.",
+      dependency.specifier
+    ))
+    .severity(DiagnosticSeverity::Warning))
+}
+
+/// Whether the dependency is a relative specifier without a file extension, such as `./x`
+fn is_missing_extension(dependency: &Dependency) -> bool {
+  let specifier = dependency.specifier.as_str();
+
+  (specifier.starts_with("./") || specifier.starts_with("../"))
+    && Path::new(specifier).extension().is_none()
+}
+
+fn make_missing_extension_diagnostic(asset: &Asset, dependency: &Dependency) -> Diagnostic {
+  let code_highlights = dependency
+    .loc
+    .clone()
+    .map(|loc| vec![CodeHighlight::from(loc)])
+    .unwrap_or_default();
+
+  // The location covers the quotes of the specifier, so the replacement keeps the same quotes
+  let suggestions = dependency
+    .loc
+    .clone()
+    .map(|loc| {
+      let quote = specifier_quote(&asset.code.to_string(), &loc);
+      let replacement = format!("{quote}{}.js{quote}", dependency.specifier);
+
+      vec![CodeSuggestion { loc, replacement }]
+    })
+    .unwrap_or_default();
+
+  diagnostic!(DiagnosticBuilder::default()
+    .code_frames(vec![make_code_frame(asset, code_highlights)])
+    .hints(vec![String::from(
+      "Add the file extension, such as `.js`, which ES modules require outside of Parcel."
+    )])
+    .message(format!(
+      "The import of '{}' is missing a file extension.",
+      dependency.specifier
+    ))
+    .severity(DiagnosticSeverity::Warning)
+    .suggestions(suggestions))
+}
+
+/// The quote character that the string literal at `loc` starts with, or `"` if it is not found
+fn specifier_quote(code: &str, loc: &SourceLocation) -> char {
+  code
+    .lines()
+    .nth(loc.start.line.saturating_sub(1))
+    .and_then(|line| line.chars().nth(loc.start.column.saturating_sub(1)))
+    .filter(|c| *c == '"' || *c == '\'')
+    .unwrap_or('"')
+}
+
+/// The number of `..` segments in a relative specifier, such as 2 for `../../x`
+fn relative_depth(specifier: &str) -> usize {
+  if !specifier.starts_with("./") && !specifier.starts_with("../") {
+    return 0;
+  }
+
+  specifier
+    .split('/')
+    .filter(|segment| *segment == "..")
+    .count()
+}
+
+fn make_relative_depth_diagnostic(
+  asset: &Asset,
+  dependency: &Dependency,
+  depth: usize,
+  limit: usize,
+) -> Diagnostic {
+  let code_highlights = dependency
+    .loc
+    .clone()
+    .map(|loc| vec![CodeHighlight::from(loc)])
+    .unwrap_or_default();
+
+  diagnostic!(DiagnosticBuilder::default()
+    .code_frames(vec![make_code_frame(asset, code_highlights)])
+    .hints(vec![String::from(
+      "Configure an alias for the imported directory, rather than importing it relatively."
+    )])
+    .message(format!(
+      "The import of '{}' goes up {} directories, which is more than the limit of {}.",
+      dependency.specifier, depth, limit
+    ))
+    .severity(DiagnosticSeverity::Warning))
+}
+
+fn make_dependency_count_diagnostic(asset: &Asset, count: usize, limit: usize) -> Diagnostic {
+  diagnostic!(DiagnosticBuilder::default()
+    .code_frames(vec![make_code_frame(asset, Vec::new())])
+    .hints(vec![String::from(
+      "Import from the modules that define these values directly, rather than through a re-exporting index file."
+    )])
+    .message(format!(
+      "This file has {} dependencies, which is more than the limit of {}.",
+      count, limit
+    ))
+    .severity(DiagnosticSeverity::Warning))
+}
+
+fn make_source_map_size_diagnostic(asset: &Asset, size: usize, limit: usize) -> Diagnostic {
+  diagnostic!(DiagnosticBuilder::default()
+    .code_frames(vec![make_code_frame(asset, Vec::new())])
+    .hints(vec![String::from(
+      "Raise maxSourceMapBytes to keep the source maps of large files."
+    )])
+    .message(format!(
+      "The source map of this file is {} bytes, which is more than the limit of {}, so it was dropped.",
+      size, limit
+    ))
+    .severity(DiagnosticSeverity::Warning))
+}
+
+/// Report a name that is exported more than once, along with both of its locations
+fn make_duplicate_export_diagnostic(
+  asset: &Asset,
+  duplicate: &parcel_js_swc_core::CollectDuplicateExport,
+) -> Diagnostic {
+  let code_highlights = [&duplicate.first, &duplicate.loc]
+    .into_iter()
+    .map(|loc| CodeHighlight::from(convert_loc(asset.file_path.clone(), loc)))
+    .collect();
+
+  diagnostic!(DiagnosticBuilder::default()
+    .code_frames(vec![make_code_frame(asset, code_highlights)])
+    .hints(vec![String::from("Rename one of the exports.")])
+    .message(format!("Duplicate export '{}'.", duplicate.exported)))
+}
+
+/// Find the chunk name in a `/* webpackChunkName: "name" */` comment within the parentheses of a
+/// dynamic import, before the specifier at `loc`
+fn parse_webpack_chunk_name(code: &str, loc: &SourceLocation) -> Option<String> {
+  let line_offset: usize = code
+    .split_inclusive('\n')
+    .take(loc.start.line.checked_sub(1)?)
+    .map(str::len)
+    .sum();
+  let column_offset: usize = code[line_offset..]
+    .chars()
+    .take(loc.start.column.checked_sub(1)?)
+    .map(char::len_utf8)
+    .sum();
+
+  let before_specifier = code.get(..line_offset + column_offset)?;
+  let arguments = &before_specifier[before_specifier.rfind('(')? + 1..];
+  let comment = arguments.trim().strip_prefix("/*")?.strip_suffix("*/")?;
+
+  let value = comment
+    .split_once("webpackChunkName")?
+    .1
+    .trim_start()
+    .strip_prefix(':')?
+    .trim_start();
+  let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+  let (name, _) = value[1..].split_once(quote)?;
+
+  Some(name.to_string())
+}
+
+/// CSS modules are identified by their `.module.css` extension, ignoring any query or hash
+fn is_css_module_specifier(specifier: &str) -> bool {
+  specifier
+    .split(['?', '#'])
+    .next()
+    .is_some_and(|path| path.ends_with(".module.css"))
+}
+
+fn make_unused_import_diagnostic(
+  asset: &Asset,
+  import: &parcel_js_swc_core::CollectImportedSymbol,
+) -> Diagnostic {
+  let loc = convert_loc(asset.file_path.clone(), &import.loc);
+
+  diagnostic!(DiagnosticBuilder::default()
+    .code_frames(vec![make_code_frame(asset, vec![CodeHighlight::from(loc)])])
+    .hints(vec![String::from("Remove the import if it is not needed.")])
+    .message(format!(
+      "`{}` is imported from '{}' but never used.",
+      import.local, import.source
+    ))
+    .severity(DiagnosticSeverity::Info))
+}
+
+/// The automatic JSX runtime imports its own helpers, so a default import of React is only needed
+/// when it is referenced directly
+fn is_redundant_react_import(
+  asset: &Asset,
+  options: &ParcelOptions,
+  import: &parcel_js_swc_core::CollectImportedSymbol,
+) -> bool {
+  options.automatic_jsx_runtime
+    && matches!(asset.asset_type, FileType::Jsx | FileType::Tsx)
+    && &*import.source == "react"
+    && &*import.imported == "default"
+}
+
+fn make_redundant_react_import_diagnostic(
+  asset: &Asset,
+  import: &parcel_js_swc_core::CollectImportedSymbol,
+) -> Diagnostic {
+  let loc = convert_loc(asset.file_path.clone(), &import.loc);
+
+  diagnostic!(DiagnosticBuilder::default()
+    .code_frames(vec![make_code_frame(asset, vec![CodeHighlight::from(loc)])])
+    .hints(vec![String::from(
+      "Remove the import, the automatic JSX runtime does not use it."
+    )])
+    .message(format!(
+      "`{}` is imported from 'react' but is not needed by the automatic JSX runtime.",
+      import.local
+    ))
+    .severity(DiagnosticSeverity::Warning))
+}
+
+/// Prepend `banner` onto the code of the asset on its own lines
+///
+/// The source map is moved down by the number of lines the banner takes up, so that it keeps
+/// pointing at the same code. Locations in the original source, such as those of dependencies,
+/// are not affected.
+fn prepend_banner(asset: &mut Asset, banner: &str) {
+  let banner = banner.trim_end();
+  asset.code = Arc::new(Code::from(format!("{}\n{}", banner, asset.code)));
+
+  // A source map that cannot be read would point at the wrong lines, so it is dropped
+  let line_count = banner.lines().count() as u32;
+  asset.map = asset
+    .map
+    .as_deref()
+    .and_then(|map| offset_source_map(map, line_count));
+}
+
+/// Move the generated positions of the source map `map` down by `line_count` lines
+fn offset_source_map(map: &str, line_count: u32) -> Option<String> {
+  let map = SourceMap::from_slice(map.as_bytes()).ok()?;
+
+  let tokens = map
+    .tokens()
+    .map(|token| RawToken {
+      dst_line: token.get_dst_line() + line_count,
+      ..token.get_raw_token()
+    })
+    .collect();
+  let sources_content = map
+    .source_contents()
+    .any(|content| content.is_some())
+    .then(|| {
+      map
+        .source_contents()
+        .map(|content| content.map(|content| content.to_string().into()))
+        .collect()
+    });
+
+  let map = SourceMap::new(
+    map.get_file().map(|file| file.to_string().into()),
+    tokens,
+    map.names().map(|name| name.to_string().into()).collect(),
+    map
+      .sources()
+      .map(|source| source.to_string().into())
+      .collect(),
+    sources_content,
+  );
+
+  let mut buffer = Vec::new();
+  map.to_writer(&mut buffer).ok()?;
+  String::from_utf8(buffer).ok()
+}
+
+/// Sort symbols by their exported name, then by their local name
+fn sort_symbols(symbols: &mut [Symbol]) {
+  symbols.sort_by(|a, b| {
+    a.exported
+      .cmp(&b.exported)
+      .then_with(|| a.local.cmp(&b.local))
+  });
+}
+
+/// Whether the dependency is likely to resolve to a CommonJS module
+///
+/// Dependencies are not resolved yet at this point, so this conservatively only considers
+/// specifiers with a `.cjs` extension.
+fn is_likely_commonjs(dependency: &Dependency) -> bool {
+  dependency.specifier.ends_with(".cjs")
+}
+
+/// Default imports of CommonJS modules without `__esModule` get the whole `module.exports` object,
+/// which can be surprising
+fn make_commonjs_default_import_diagnostic(
+  asset: &Asset,
+  dependency: &Dependency,
+  symbol: &Symbol,
+) -> Diagnostic {
+  let code_highlights = symbol
+    .loc
+    .clone()
+    .map(|loc| vec![CodeHighlight::from(loc)])
+    .unwrap_or_default();
+
+  diagnostic!(DiagnosticBuilder::default()
+    .code_frames(vec![make_code_frame(asset, code_highlights)])
+    .hints(vec![
+      String::from("The default export is `module.exports`, unless the module sets `__esModule`."),
+      format!(
+        "Use a namespace import instead: `import * as name from '{}'`",
+        dependency.specifier
+      ),
+    ])
+    .message(format!(
+      "Default import of CommonJS module '{}' relies on interop.",
+      dependency.specifier
+    ))
+    .severity(DiagnosticSeverity::Warning))
+}
+
+/// The same asset may be built for multiple targets with otherwise identical environments, so the
+/// target name is used as a prefix when available to keep the keys of each target apart.
+fn make_unique_key(env: &Environment, asset_id: u64, delimiter: Option<char>) -> String {
+  match &env.target_name {
+    Some(target_name) => format!(
+      "{}{}{:016x}",
+      target_name,
+      delimiter.unwrap_or(':'),
+      asset_id
+    ),
+    None => format!("{:016x}", asset_id),
+  }
+}
+
 /// "Export star" symbol is added as a placeholder for assets that may have symbols that aren't
 /// explicitly listed. This is used to avoid errors if a symbol that hasn't been statically
 /// analyzed is accessed.
@@ -334,16 +873,18 @@ fn make_export_star_symbol(asset_id: u64) -> Symbol {
 
 fn make_esm_helpers_dependency(
   options: &ParcelOptions,
-  asset_file_path: &PathBuf,
+  source_path: &Path,
+  asset_type: FileType,
   asset_environment: Environment,
   has_symbols: bool,
   asset_id: u64,
 ) -> Dependency {
   Dependency {
     source_asset_id: Some(format!("{:016x}", asset_id)),
+    source_asset_type: Some(asset_type),
     specifier: "@parcel/transformer-js/src/esmodule-helpers.js".into(),
     specifier_type: SpecifierType::Esm,
-    source_path: Some(asset_file_path.clone()),
+    source_path: Some(source_path.to_path_buf()),
     env: Environment {
       include_node_modules: IncludeNodeModules::Map(
         [("@parcel/transformer-js".to_string(), true)]
@@ -386,6 +927,8 @@ fn convert_dependency(
   transformer_config: &parcel_js_swc_core::Config,
   asset: &Asset,
   asset_id: u64,
+  options: &ParcelOptions,
+  source_path: &Path,
   transformer_dependency: parcel_js_swc_core::DependencyDescriptor,
 ) -> Result<DependencyConversionResult, Vec<Diagnostic>> {
   use parcel_js_swc_core::DependencyKind;
@@ -395,10 +938,14 @@ fn convert_dependency(
     env: asset.env.clone(),
     loc: Some(loc.clone()),
     priority: convert_priority(&transformer_dependency),
+    // Dependencies are still resolved from the absolute path when the relative one is stored
+    resolve_from: (source_path != asset.file_path).then(|| asset.file_path.clone()),
     source_asset_id: Some(format!("{:016x}", asset_id)),
-    source_path: Some(asset.file_path.clone()),
+    source_asset_type: Some(asset.asset_type.clone()),
+    source_path: Some(source_path.to_path_buf()),
     specifier: transformer_dependency.specifier.as_ref().into(),
     specifier_type: convert_specifier_type(&transformer_dependency),
+    is_context: transformer_dependency.is_context,
     ..Dependency::default()
   };
 
@@ -448,7 +995,7 @@ fn convert_dependency(
           output_format,
           source_map: asset.env.source_map.clone(),
           source_type,
-          ..*asset.env.clone()
+          ..(*asset.env).clone()
         }),
         ..base_dependency
       };
@@ -465,7 +1012,7 @@ fn convert_dependency(
           output_format: OutputFormat::Global,
           source_map: asset.env.source_map.clone(),
           source_type,
-          ..*asset.env.clone()
+          ..(*asset.env).clone()
         }),
         needs_stable_name: true,
         // placeholder: dep.placeholder.map(|s| s.into()),
@@ -484,7 +1031,7 @@ fn convert_dependency(
           output_format: OutputFormat::EsModule,
           source_map: asset.env.source_map.clone(),
           source_type: SourceType::Module,
-          ..*asset.env.clone()
+          ..(*asset.env).clone()
         }),
         // flags: dep_flags,
         // placeholder: dep.placeholder.map(|s| s.into()),
@@ -498,6 +1045,7 @@ fn convert_dependency(
       let dependency = Dependency {
         env: asset.env.clone(),
         bundle_behavior: BundleBehavior::Isolated,
+        resolve_only: transformer_dependency.is_resolve_only,
         // flags: dep_flags,
         // placeholder: dep.placeholder.map(|s| s.into()),
         ..base_dependency
@@ -533,13 +1081,7 @@ fn convert_dependency(
           EnvironmentContext::Worklet | EnvironmentContext::ServiceWorker
         ) {
           let diagnostic = diagnostic!(DiagnosticBuilder::default()
-            .code_frames(vec![CodeFrame {
-              code_highlights: vec![CodeHighlight::from(loc)],
-              ..CodeFrame::from(File {
-                contents: asset.code.to_string(),
-                path: asset.file_path.clone()
-              })
-            }])
+            .code_frames(vec![make_code_frame(asset, vec![CodeHighlight::from(loc)])])
             .hints(vec![String::from("Try using a static `import`")])
             .message(format!(
               "import() is not allowed in {}.",
@@ -574,19 +1116,40 @@ fn convert_dependency(
             output_format,
             source_map: env.source_map.clone(),
             source_type: SourceType::Module,
-            ..*env
+            ..(*env).clone()
           });
         }
       }
 
+      let chunk_name = if transformer_dependency.kind == DependencyKind::DynamicImport {
+        std::str::from_utf8(asset.code.bytes())
+          .ok()
+          .and_then(|code| parse_webpack_chunk_name(code, &loc))
+      } else {
+        None
+      };
+
+      let import_attributes = transformer_dependency
+        .import_attributes
+        .iter()
+        .flatten()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+
       let dependency = Dependency {
+        chunk_name,
         env,
+        import_attributes,
+        is_css_module: is_css_module_specifier(&transformer_dependency.specifier),
         is_optional: transformer_dependency.is_optional,
         is_esm: matches!(
           transformer_dependency.kind,
           DependencyKind::Import | DependencyKind::Export
         ),
         placeholder: transformer_dependency.placeholder.clone(),
+        preload: options.preload_unawaited_imports
+          && transformer_dependency.kind == DependencyKind::DynamicImport
+          && !transformer_dependency.is_awaited,
         ..base_dependency
       };
 
@@ -605,10 +1168,373 @@ fn convert_source_type(source_type: &Option<parcel_js_swc_core::SourceType>) ->
 
 #[cfg(test)]
 mod test {
-  use crate::transformer::test_helpers::run_swc_core_transform;
+  use std::collections::BTreeMap;
+
+  use parcel_core::types::Location;
+
+  use crate::transformer::test_helpers::{make_test_swc_config, run_swc_core_transform};
 
   use super::*;
 
+  #[test]
+  fn test_parse_webpack_chunk_name() {
+    let loc = |line: usize, column: usize| SourceLocation {
+      start: Location { line, column },
+      ..SourceLocation::default()
+    };
+
+    assert_eq!(
+      parse_webpack_chunk_name(
+        r#"import(/* webpackChunkName: "foo" */ './m');"#,
+        &loc(1, 38)
+      ),
+      Some(String::from("foo"))
+    );
+    assert_eq!(
+      parse_webpack_chunk_name(
+        "const m = import(\n  /* webpackPrefetch: true, webpackChunkName: 'bar' */\n  './m'\n);",
+        &loc(3, 3)
+      ),
+      Some(String::from("bar"))
+    );
+    assert_eq!(
+      parse_webpack_chunk_name(r#"import(/* other */ './m');"#, &loc(1, 20)),
+      None
+    );
+    assert_eq!(parse_webpack_chunk_name("import('./m');", &loc(1, 8)), None);
+  }
+
+  #[test]
+  fn test_duplicate_export_is_an_error() {
+    let source = "export const a = 1;\nconst b = 2;\nexport { b as a };";
+    let asset = Asset {
+      code: Arc::new(Code::from(String::from(source))),
+      file_path: PathBuf::from("something/file.js"),
+      ..Asset::default()
+    };
+
+    let diagnostics = convert_result(
+      asset,
+      &parcel_js_swc_core::Config::default(),
+      run_swc_core_transform(source),
+      &ParcelOptions::default(),
+      Path::new(""),
+      &|_| {},
+    )
+    .unwrap_err();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Duplicate export 'a'.");
+
+    let highlights = &diagnostics[0].code_frames[0].code_highlights;
+    assert_eq!(highlights.len(), 2);
+    assert_eq!(highlights[0].start.line, 1);
+    assert_eq!(highlights[1].start.line, 3);
+  }
+
+  #[test]
+  fn test_imports_of_the_same_specifier_are_coalesced() {
+    let source = "import a from './x';\nimport { b } from './x';\nconsole.log(a, b);";
+    let asset = Asset {
+      code: Arc::new(Code::from(String::from(source))),
+      file_path: PathBuf::from("something/file.js"),
+      ..Asset::default()
+    };
+
+    let result = convert_result(
+      asset,
+      &parcel_js_swc_core::Config::default(),
+      run_swc_core_transform(source),
+      &ParcelOptions::default(),
+      Path::new(""),
+      &|_| {},
+    )
+    .unwrap();
+
+    let dependencies: Vec<&Dependency> = result
+      .dependencies
+      .iter()
+      .filter(|dependency| dependency.specifier == "./x")
+      .collect();
+
+    assert_eq!(dependencies.len(), 1);
+    assert_eq!(dependencies[0].loc.as_ref().unwrap().start.line, 1);
+
+    let mut exported: Vec<&str> = dependencies[0]
+      .symbols
+      .iter()
+      .map(|symbol| symbol.exported.as_str())
+      .collect();
+    exported.sort();
+
+    assert_eq!(exported, vec!["b", "default"]);
+  }
+
+  #[test]
+  fn test_relative_file_paths() {
+    let run = |relative_file_paths: bool| {
+      let source = "import './dep';";
+      let asset = Asset {
+        code: Arc::new(Code::from(String::from(source))),
+        file_path: PathBuf::from("/project/mock_path.js"),
+        ..Asset::default()
+      };
+      let options = ParcelOptions {
+        relative_file_paths,
+        ..ParcelOptions::default()
+      };
+
+      let result = convert_result(
+        asset,
+        &parcel_js_swc_core::Config::default(),
+        run_swc_core_transform(source),
+        &options,
+        Path::new("/project"),
+        &|_| {},
+      )
+      .unwrap();
+      let dependency = result.dependencies[0].clone();
+
+      (dependency.source_path, dependency.resolve_from)
+    };
+
+    assert_eq!(
+      run(true),
+      (
+        Some(PathBuf::from("mock_path.js")),
+        Some(PathBuf::from("/project/mock_path.js"))
+      )
+    );
+    assert_eq!(
+      run(false),
+      (Some(PathBuf::from("/project/mock_path.js")), None)
+    );
+  }
+
+  #[test]
+  fn test_relative_source_paths() {
+    let source = "import './dep';";
+    let asset = Asset {
+      code: Arc::new(Code::from(String::from(source))),
+      file_path: PathBuf::from("/project/mock_path.js"),
+      ..Asset::default()
+    };
+    let options = ParcelOptions {
+      relative_source_paths: true,
+      ..ParcelOptions::default()
+    };
+
+    let result = convert_result(
+      asset,
+      &parcel_js_swc_core::Config::default(),
+      run_swc_core_transform(source),
+      &options,
+      Path::new("/project"),
+      &|_| {},
+    )
+    .unwrap();
+    let dependency = result.dependencies[0].clone();
+
+    assert_eq!(dependency.source_path, Some(PathBuf::from("mock_path.js")));
+    assert_eq!(
+      dependency.resolve_from,
+      Some(PathBuf::from("/project/mock_path.js"))
+    );
+  }
+
+  #[test]
+  fn test_dynamic_import_attributes() {
+    let source = "const data = import('./data.json', { with: { type: 'json' } });";
+    let asset = Asset {
+      code: Arc::new(Code::from(String::from(source))),
+      file_path: PathBuf::from("something/file.js"),
+      ..Asset::default()
+    };
+
+    let result = convert_result(
+      asset,
+      &parcel_js_swc_core::Config::default(),
+      run_swc_core_transform(source),
+      &ParcelOptions::default(),
+      Path::new(""),
+      &|_| {},
+    )
+    .unwrap();
+
+    let dependency = result
+      .dependencies
+      .iter()
+      .find(|dependency| dependency.specifier == "./data.json")
+      .unwrap();
+
+    assert_eq!(
+      dependency.import_attributes,
+      BTreeMap::from([(String::from("type"), String::from("json"))])
+    );
+  }
+
+  #[test]
+  fn test_context_dynamic_import() {
+    let source = "const locale = import(`./locales/${lang}.js`);";
+    let asset = Asset {
+      code: Arc::new(Code::from(String::from(source))),
+      file_path: PathBuf::from("something/file.js"),
+      ..Asset::default()
+    };
+    let config = parcel_js_swc_core::Config {
+      context_dynamic_imports: true,
+      ..make_test_swc_config(source)
+    };
+
+    let result = convert_result(
+      asset,
+      &config,
+      parcel_js_swc_core::transform(config.clone(), None).unwrap(),
+      &ParcelOptions::default(),
+      Path::new(""),
+      &|_| {},
+    )
+    .unwrap();
+
+    assert_eq!(result.dependencies.len(), 1);
+    assert_eq!(result.dependencies[0].specifier, "./locales/*.js");
+    assert_eq!(result.dependencies[0].priority, Priority::Lazy);
+    assert!(result.dependencies[0].is_context);
+  }
+
+  #[test]
+  fn test_import_meta_resolve_is_resolve_only() {
+    let source = "const url = import.meta.resolve('./m');";
+    let asset = Asset {
+      code: Arc::new(Code::from(String::from(source))),
+      file_path: PathBuf::from("something/file.js"),
+      ..Asset::default()
+    };
+
+    let result = convert_result(
+      asset,
+      &parcel_js_swc_core::Config::default(),
+      run_swc_core_transform(source),
+      &ParcelOptions::default(),
+      Path::new(""),
+      &|_| {},
+    )
+    .unwrap();
+
+    assert_eq!(result.dependencies.len(), 1);
+    assert_eq!(result.dependencies[0].specifier, "./m");
+    assert_eq!(result.dependencies[0].specifier_type, SpecifierType::Url);
+    assert!(result.dependencies[0].resolve_only);
+  }
+
+  /// The symbol of the `./x` dependency and the asset symbol it is re-exported as
+  fn re_export_symbols(source: &str, exported: &str, scope_hoist: bool) -> (Symbol, Symbol) {
+    let asset = Asset {
+      code: Arc::new(Code::from(String::from(source))),
+      file_path: PathBuf::from("something/file.js"),
+      ..Asset::default()
+    };
+    let config = parcel_js_swc_core::Config {
+      scope_hoist,
+      ..make_test_swc_config(source)
+    };
+
+    let result = convert_result(
+      asset,
+      &config,
+      parcel_js_swc_core::transform(config.clone(), None).unwrap(),
+      &ParcelOptions::default(),
+      Path::new(""),
+      &|_| {},
+    )
+    .unwrap();
+
+    let dependency = result
+      .dependencies
+      .iter()
+      .find(|dependency| dependency.specifier == "./x")
+      .unwrap();
+    let asset_symbol = result
+      .asset
+      .symbols
+      .iter()
+      .find(|symbol| symbol.exported == exported)
+      .unwrap();
+    let dependency_symbol = dependency
+      .symbols
+      .iter()
+      .find(|symbol| symbol.local == asset_symbol.local)
+      .unwrap();
+
+    (dependency_symbol.clone(), asset_symbol.clone())
+  }
+
+  #[test]
+  fn test_default_re_export() {
+    for scope_hoist in [true, false] {
+      let (dependency_symbol, asset_symbol) =
+        re_export_symbols("export { default } from './x';", "default", scope_hoist);
+
+      assert_eq!(dependency_symbol.exported, "default");
+      assert_eq!(asset_symbol.exported, "default");
+      assert!(dependency_symbol.is_weak);
+    }
+  }
+
+  #[test]
+  fn test_export_default_from() {
+    for scope_hoist in [true, false] {
+      let (dependency_symbol, asset_symbol) =
+        re_export_symbols("export foo from './x';", "foo", scope_hoist);
+
+      assert_eq!(dependency_symbol.exported, "default");
+      assert_eq!(asset_symbol.exported, "foo");
+    }
+  }
+
+  #[test]
+  fn test_namespace_re_export() {
+    let source = "export * as ns from './x';";
+    let asset = Asset {
+      code: Arc::new(Code::from(String::from(source))),
+      file_path: PathBuf::from("something/file.js"),
+      ..Asset::default()
+    };
+
+    let result = convert_result(
+      asset,
+      &parcel_js_swc_core::Config::default(),
+      run_swc_core_transform(source),
+      &ParcelOptions::default(),
+      Path::new(""),
+      &|_| {},
+    )
+    .unwrap();
+
+    let dependency = result
+      .dependencies
+      .iter()
+      .find(|dependency| dependency.specifier == "./x")
+      .unwrap();
+
+    let dependency_symbol = dependency
+      .symbols
+      .iter()
+      .find(|symbol| symbol.exported == "*")
+      .unwrap();
+
+    let asset_symbol = result
+      .asset
+      .symbols
+      .iter()
+      .find(|symbol| symbol.exported == "ns")
+      .unwrap();
+
+    assert_eq!(asset_symbol.local, dependency_symbol.local);
+    assert!(asset_symbol.local.ends_with("$re_export$ns"));
+    assert!(dependency_symbol.is_weak);
+  }
+
   #[test]
   fn test_is_re_export_all_symbol() {
     let source = r#"
@@ -7,7 +7,9 @@ pub(crate) fn convert_priority(
 
   match transformer_dependency.kind {
     DependencyKind::DynamicImport => Priority::Lazy,
-    DependencyKind::WebWorker => Priority::Lazy,
+    // Worker scripts are fetched as soon as the worker is constructed, so these are loaded in
+    // parallel with the bundle that creates it
+    DependencyKind::WebWorker => Priority::Parallel,
     DependencyKind::ServiceWorker => Priority::Lazy,
     DependencyKind::Worklet => Priority::Lazy,
     DependencyKind::Url => Priority::Lazy,
@@ -99,7 +101,7 @@ mod test {
     "#,
     );
     assert_eq!(dependency.kind, DependencyKind::WebWorker);
-    assert_eq!(convert_priority(&dependency), Priority::Lazy);
+    assert_eq!(convert_priority(&dependency), Priority::Parallel);
     assert_eq!(convert_specifier_type(&dependency), SpecifierType::Url);
   }
 
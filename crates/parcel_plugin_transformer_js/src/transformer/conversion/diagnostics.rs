@@ -0,0 +1,75 @@
+use parcel_core::diagnostic;
+use parcel_core::types::{
+  Asset, CodeFrame, CodeHighlight, Diagnostic, DiagnosticBuilder, DiagnosticSeverity, File,
+  SourceType,
+};
+
+use crate::transformer::conversion::loc::convert_loc;
+
+/// Build a code frame for the original source code of `asset` with the given highlights.
+pub(crate) fn make_code_frame(asset: &Asset, code_highlights: Vec<CodeHighlight>) -> CodeFrame {
+  CodeFrame {
+    code_highlights,
+    ..CodeFrame::from(File {
+      contents: asset.code.to_string(),
+      path: asset.file_path.clone(),
+    })
+  }
+}
+
+/// Convert a diagnostic emitted by the SWC transformer into a [`Diagnostic`].
+pub(crate) fn convert_diagnostic(
+  asset: &Asset,
+  diagnostic: parcel_js_swc_core::Diagnostic,
+) -> Diagnostic {
+  let code_highlights = diagnostic
+    .code_highlights
+    .unwrap_or_default()
+    .into_iter()
+    .map(|highlight| CodeHighlight {
+      message: highlight.message,
+      ..CodeHighlight::from(convert_loc(asset.file_path.clone(), &highlight.loc))
+    })
+    .collect();
+
+  diagnostic!(DiagnosticBuilder::default()
+    .code_frames(vec![make_code_frame(asset, code_highlights)])
+    .documentation_url(diagnostic.documentation_url)
+    .hints(diagnostic.hints.unwrap_or_default())
+    .message(diagnostic.message)
+    .severity(convert_severity(&diagnostic.severity)))
+}
+
+/// Convert an error emitted by the SWC transformer, which fails the transformation.
+///
+/// Syntax that is only invalid in strict mode, such as `with` statements, gets a hint explaining
+/// why it is rejected in ES modules.
+pub(crate) fn convert_error(
+  asset: &Asset,
+  diagnostic: parcel_js_swc_core::Diagnostic,
+) -> Diagnostic {
+  let mut error = convert_diagnostic(asset, diagnostic);
+
+  if asset.env.source_type == SourceType::Module && error.message.contains("strict mode") {
+    error.hints.push(String::from(
+      "ES modules are always in strict mode. Remove the construct, or load this file as a classic script.",
+    ));
+  }
+
+  error.severity = DiagnosticSeverity::Error;
+  error
+}
+
+/// Source errors are always treated as errors here, since the transformer does not know yet
+/// whether the asset is part of the project or not.
+pub(crate) fn convert_severity(
+  severity: &parcel_js_swc_core::DiagnosticSeverity,
+) -> DiagnosticSeverity {
+  use parcel_js_swc_core::DiagnosticSeverity as SwcDiagnosticSeverity;
+
+  match severity {
+    SwcDiagnosticSeverity::Error => DiagnosticSeverity::Error,
+    SwcDiagnosticSeverity::SourceError => DiagnosticSeverity::Error,
+    SwcDiagnosticSeverity::Warning => DiagnosticSeverity::Warning,
+  }
+}
@@ -0,0 +1,31 @@
+use std::path::Path;
+
+use anyhow::Error;
+
+use parcel_core::diagnostic_error;
+use parcel_core::types::{CodeFrame, DiagnosticBuilder, Environment, SourceType};
+
+/// Check that the options of the environment an asset is transformed in do not conflict
+///
+/// Some combinations are not supported by the SWC transformer, and would otherwise be silently
+/// ignored. This reports all of the conflicting fields instead. It checks the environment as it was
+/// configured, before the transformer derives a source type from the file extension.
+pub(crate) fn validate_environment(env: &Environment, file_path: &Path) -> Result<(), Error> {
+  let mut conflicts = Vec::new();
+
+  if env.should_scope_hoist && env.source_type == SourceType::Script {
+    conflicts.push("`should_scope_hoist` cannot be enabled when `source_type` is `Script`");
+  }
+
+  if conflicts.is_empty() {
+    return Ok(());
+  }
+
+  Err(diagnostic_error!(DiagnosticBuilder::default()
+    .code_frames(vec![CodeFrame::from(file_path.to_path_buf())])
+    .hints(conflicts.into_iter().map(String::from).collect())
+    .message(format!(
+      "Invalid JavaScript transformer environment for {}.",
+      file_path.display()
+    ))))
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use swc_core::common::Span;
+use swc_core::common::Spanned;
+use swc_core::common::DUMMY_SP;
+use swc_core::ecma::ast::BlockStmt;
+use swc_core::ecma::ast::DoWhileStmt;
+use swc_core::ecma::ast::EmptyStmt;
+use swc_core::ecma::ast::ForInStmt;
+use swc_core::ecma::ast::ForOfStmt;
+use swc_core::ecma::ast::ForStmt;
+use swc_core::ecma::ast::IfStmt;
+use swc_core::ecma::ast::Stmt;
+use swc_core::ecma::ast::WhileStmt;
+use swc_core::ecma::visit::Visit;
+use swc_core::ecma::visit::VisitMut;
+use swc_core::ecma::visit::VisitMutWith;
+use swc_core::ecma::visit::VisitWith;
+
+/// Keeps the braces of `if` and loop bodies that contain a single statement
+///
+/// The dead branch remover unwraps these bodies, so that `if (a) { b(); }` is emitted as
+/// `if (a) b();`. The blocks are recorded by visiting the module before it is simplified, and put
+/// back by visiting it mutably afterwards.
+#[derive(Default)]
+pub struct BlockPreserver {
+  /// The span of each recorded block, by the span of its only statement
+  blocks: HashMap<Span, Span>,
+}
+
+impl BlockPreserver {
+  fn record(&mut self, body: &Stmt) {
+    if let Stmt::Block(BlockStmt { span, stmts }) = body {
+      if let [stmt] = stmts.as_slice() {
+        if !stmt.span().is_dummy() {
+          self.blocks.insert(stmt.span(), *span);
+        }
+      }
+    }
+  }
+
+  fn restore(&self, body: &mut Stmt) {
+    if matches!(body, Stmt::Block(_)) {
+      return;
+    }
+
+    if let Some(span) = self.blocks.get(&body.span()) {
+      let stmt = std::mem::replace(body, Stmt::Empty(EmptyStmt { span: DUMMY_SP }));
+      *body = Stmt::Block(BlockStmt {
+        span: *span,
+        stmts: vec![stmt],
+      });
+    }
+  }
+}
+
+impl Visit for BlockPreserver {
+  fn visit_if_stmt(&mut self, node: &IfStmt) {
+    self.record(&node.cons);
+    if let Some(alt) = &node.alt {
+      self.record(alt);
+    }
+
+    node.visit_children_with(self);
+  }
+
+  fn visit_while_stmt(&mut self, node: &WhileStmt) {
+    self.record(&node.body);
+    node.visit_children_with(self);
+  }
+
+  fn visit_do_while_stmt(&mut self, node: &DoWhileStmt) {
+    self.record(&node.body);
+    node.visit_children_with(self);
+  }
+
+  fn visit_for_stmt(&mut self, node: &ForStmt) {
+    self.record(&node.body);
+    node.visit_children_with(self);
+  }
+
+  fn visit_for_in_stmt(&mut self, node: &ForInStmt) {
+    self.record(&node.body);
+    node.visit_children_with(self);
+  }
+
+  fn visit_for_of_stmt(&mut self, node: &ForOfStmt) {
+    self.record(&node.body);
+    node.visit_children_with(self);
+  }
+}
+
+impl VisitMut for BlockPreserver {
+  fn visit_mut_if_stmt(&mut self, node: &mut IfStmt) {
+    node.visit_mut_children_with(self);
+
+    self.restore(&mut node.cons);
+    if let Some(alt) = &mut node.alt {
+      self.restore(alt);
+    }
+  }
+
+  fn visit_mut_while_stmt(&mut self, node: &mut WhileStmt) {
+    node.visit_mut_children_with(self);
+    self.restore(&mut node.body);
+  }
+
+  fn visit_mut_do_while_stmt(&mut self, node: &mut DoWhileStmt) {
+    node.visit_mut_children_with(self);
+    self.restore(&mut node.body);
+  }
+
+  fn visit_mut_for_stmt(&mut self, node: &mut ForStmt) {
+    node.visit_mut_children_with(self);
+    self.restore(&mut node.body);
+  }
+
+  fn visit_mut_for_in_stmt(&mut self, node: &mut ForInStmt) {
+    node.visit_mut_children_with(self);
+    self.restore(&mut node.body);
+  }
+
+  fn visit_mut_for_of_stmt(&mut self, node: &mut ForOfStmt) {
+    node.visit_mut_children_with(self);
+    self.restore(&mut node.body);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use swc_core::ecma::ast::Module;
+  use swc_core::ecma::transforms::optimization::simplify::dead_branch_remover;
+  use swc_core::ecma::visit::Fold;
+  use swc_core::ecma::visit::FoldWith;
+
+  use crate::test_utils::run_fold;
+
+  use super::*;
+
+  struct RemoveDeadBranches<F: Fold>(BlockPreserver, F);
+
+  impl<F: Fold> Fold for RemoveDeadBranches<F> {
+    fn fold_module(&mut self, module: Module) -> Module {
+      module.visit_with(&mut self.0);
+      let mut module = module.fold_with(&mut self.1);
+      module.visit_mut_with(&mut self.0);
+      module
+    }
+  }
+
+  #[test]
+  fn test_block_preserver() {
+    let code = r#"
+if (a) {
+  b();
+} else {
+  c();
+}
+while (a) {
+  b();
+}
+if (false) {
+  d();
+}
+"#;
+
+    let output_code = run_fold(code, |context| {
+      RemoveDeadBranches(
+        BlockPreserver::default(),
+        dead_branch_remover(context.unresolved_mark),
+      )
+    })
+    .output_code;
+
+    assert_eq!(
+      output_code,
+      r#"
+if (a) {
+    b();
+} else {
+    c();
+}
+while(a){
+    b();
+}
+"#
+      .trim_start()
+    );
+  }
+}
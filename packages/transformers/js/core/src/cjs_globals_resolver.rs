@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+
+use swc_core::common::Mark;
+use swc_core::common::SyntaxContext;
+use swc_core::ecma::ast::Id;
+use swc_core::ecma::ast::Ident;
+use swc_core::ecma::ast::Module;
+use swc_core::ecma::atoms::JsWord;
+use swc_core::ecma::utils::collect_decls;
+use swc_core::ecma::visit::VisitMut;
+
+/// Marks the `require`, `module` and `exports` globals inserted by the TypeScript pass as unresolved
+///
+/// SWC lowers `import x = require('y')` and `export = x` to CommonJS, but gives the globals it
+/// inserts the top level mark rather than the unresolved mark. They would then be treated as top
+/// level variables, and the `require` would not be collected as a dependency. Variables with these
+/// names that are declared in the module itself are left untouched.
+pub struct CjsGlobalsResolver {
+  global_ctxt: SyntaxContext,
+  unresolved_ctxt: SyntaxContext,
+  declared: HashSet<JsWord>,
+}
+
+impl CjsGlobalsResolver {
+  pub fn new(module: &Module, global_mark: Mark, unresolved_mark: Mark) -> Self {
+    let global_ctxt = SyntaxContext::empty().apply_mark(global_mark);
+    let declared = collect_decls::<Id, _>(module)
+      .into_iter()
+      .filter(|(_, ctxt)| *ctxt == global_ctxt)
+      .map(|(sym, _)| sym)
+      .collect();
+
+    Self {
+      global_ctxt,
+      unresolved_ctxt: SyntaxContext::empty().apply_mark(unresolved_mark),
+      declared,
+    }
+  }
+}
+
+impl VisitMut for CjsGlobalsResolver {
+  fn visit_mut_ident(&mut self, node: &mut Ident) {
+    if node.span.ctxt == self.global_ctxt
+      && matches!(&*node.sym, "require" | "module" | "exports")
+      && !self.declared.contains(&node.sym)
+    {
+      node.span.ctxt = self.unresolved_ctxt;
+    }
+  }
+}
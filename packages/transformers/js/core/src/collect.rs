@@ -16,6 +16,8 @@ use swc_core::ecma::visit::Visit;
 use swc_core::ecma::visit::VisitWith;
 
 use crate::id;
+use crate::utils::export_default_keyword_span;
+use crate::utils::export_keyword_span;
 use crate::utils::is_unresolved;
 use crate::utils::match_export_name;
 use crate::utils::match_export_name_ident;
@@ -89,6 +91,8 @@ pub struct Collect {
   pub non_static_requires: HashSet<JsWord>,
   pub wrapped_requires: HashSet<String>,
   pub bailouts: Option<Vec<Bailout>>,
+  /// ES module exports whose name is already used by another ES module export, in source order
+  pub duplicate_exports: Vec<CollectDuplicateExport>,
   in_module_this: bool,
   in_top_level: bool,
   in_export_decl: bool,
@@ -117,6 +121,15 @@ pub struct CollectExportedSymbol {
   pub loc: SourceLocation,
 }
 
+/// A name that is exported by more than one ES module export
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectDuplicateExport {
+  pub exported: JsWord,
+  /// The location of the export that first used the name
+  pub first: SourceLocation,
+  pub loc: SourceLocation,
+}
+
 #[derive(Debug, Serialize)]
 pub struct CollectExportedAll {
   pub source: JsWord,
@@ -170,6 +183,7 @@ impl Collect {
       in_assign: false,
       in_class: false,
       bailouts: if trace_bailouts { Some(vec![]) } else { None },
+      duplicate_exports: Vec::new(),
     }
   }
 }
@@ -401,7 +415,7 @@ impl Visit for Collect {
           } else {
             (source, orig.sym.clone())
           };
-          self.exports.insert(
+          self.insert_export(
             exported.0.clone(),
             Export {
               specifier,
@@ -418,10 +432,11 @@ impl Visit for Collect {
           }
         }
         ExportSpecifier::Default(default) => {
-          self.exports.insert(
-            js_word!("default"),
+          // export foo from 'x' exports the default export of 'x' as foo
+          self.insert_export(
+            default.exported.sym.clone(),
             Export {
-              specifier: default.exported.sym.clone(),
+              specifier: js_word!("default"),
               loc: SourceLocation::from(&self.source_map, default.exported.span),
               source,
               is_esm: true,
@@ -435,7 +450,7 @@ impl Visit for Collect {
           }
         }
         ExportSpecifier::Namespace(namespace) => {
-          self.exports.insert(
+          self.insert_export(
             match_export_name(&namespace.name).0,
             Export {
               specifier: "*".into(),
@@ -454,7 +469,7 @@ impl Visit for Collect {
   fn visit_export_decl(&mut self, node: &ExportDecl) {
     match &node.decl {
       Decl::Class(class) => {
-        self.exports.insert(
+        self.insert_export(
           class.ident.sym.clone(),
           Export {
             specifier: class.ident.sym.clone(),
@@ -469,7 +484,7 @@ impl Visit for Collect {
           .or_insert_with(|| class.ident.sym.clone());
       }
       Decl::Fn(func) => {
-        self.exports.insert(
+        self.insert_export(
           func.ident.sym.clone(),
           Export {
             specifier: func.ident.sym.clone(),
@@ -499,14 +514,15 @@ impl Visit for Collect {
   }
 
   fn visit_export_default_decl(&mut self, node: &ExportDefaultDecl) {
+    let default_span = export_default_keyword_span(&self.source_map, node.span);
     match &node.decl {
       DefaultDecl::Class(class) => {
         if let Some(ident) = &class.ident {
-          self.exports.insert(
+          self.insert_export(
             js_word!("default"),
             Export {
               specifier: ident.sym.clone(),
-              loc: SourceLocation::from(&self.source_map, node.span),
+              loc: SourceLocation::from(&self.source_map, default_span),
               source: None,
               is_esm: true,
             },
@@ -516,11 +532,11 @@ impl Visit for Collect {
             .entry(id!(ident))
             .or_insert_with(|| js_word!("default"));
         } else {
-          self.exports.insert(
+          self.insert_export(
             js_word!("default"),
             Export {
               specifier: js_word!("default"),
-              loc: SourceLocation::from(&self.source_map, node.span),
+              loc: SourceLocation::from(&self.source_map, default_span),
               source: None,
               is_esm: true,
             },
@@ -529,11 +545,11 @@ impl Visit for Collect {
       }
       DefaultDecl::Fn(func) => {
         if let Some(ident) = &func.ident {
-          self.exports.insert(
+          self.insert_export(
             js_word!("default"),
             Export {
               specifier: ident.sym.clone(),
-              loc: SourceLocation::from(&self.source_map, node.span),
+              loc: SourceLocation::from(&self.source_map, default_span),
               source: None,
               is_esm: true,
             },
@@ -543,11 +559,11 @@ impl Visit for Collect {
             .entry(id!(ident))
             .or_insert_with(|| js_word!("default"));
         } else {
-          self.exports.insert(
+          self.insert_export(
             js_word!("default"),
             Export {
               specifier: js_word!("default"),
-              loc: SourceLocation::from(&self.source_map, node.span),
+              loc: SourceLocation::from(&self.source_map, default_span),
               source: None,
               is_esm: true,
             },
@@ -563,11 +579,12 @@ impl Visit for Collect {
   }
 
   fn visit_export_default_expr(&mut self, node: &ExportDefaultExpr) {
-    self.exports.insert(
+    let default_span = export_default_keyword_span(&self.source_map, node.span);
+    self.insert_export(
       js_word!("default"),
       Export {
         specifier: js_word!("default"),
-        loc: SourceLocation::from(&self.source_map, node.span),
+        loc: SourceLocation::from(&self.source_map, default_span),
         source: None,
         is_esm: true,
       },
@@ -579,7 +596,10 @@ impl Visit for Collect {
   fn visit_export_all(&mut self, node: &ExportAll) {
     self.exports_all.insert(
       node.src.value.clone(),
-      SourceLocation::from(&self.source_map, node.span),
+      SourceLocation::from(
+        &self.source_map,
+        export_keyword_span(&self.source_map, node.span),
+      ),
     );
   }
 
@@ -594,7 +614,7 @@ impl Visit for Collect {
 
   fn visit_binding_ident(&mut self, node: &BindingIdent) {
     if self.in_export_decl {
-      self.exports.insert(
+      self.insert_export(
         node.id.sym.clone(),
         Export {
           specifier: node.id.sym.clone(),
@@ -620,7 +640,7 @@ impl Visit for Collect {
 
   fn visit_assign_pat_prop(&mut self, node: &AssignPatProp) {
     if self.in_export_decl {
-      self.exports.insert(
+      self.insert_export(
         node.key.sym.clone(),
         Export {
           specifier: node.key.sym.clone(),
@@ -669,7 +689,7 @@ impl Visit for Collect {
       () => {
         self.has_cjs_exports = true;
         if let Some((name, span)) = match_property_name(&node) {
-          self.exports.insert(
+          self.insert_export(
             name.clone(),
             Export {
               specifier: name,
@@ -969,6 +989,32 @@ impl Visit for Collect {
 }
 
 impl Collect {
+  /// The ES module imports whose local binding is never referenced, in source order
+  ///
+  /// Namespace imports are not included, as their usage is not tracked.
+  pub fn unused_imports(&self) -> Vec<CollectImportedSymbol> {
+    let mut unused_imports: Vec<CollectImportedSymbol> = self
+      .imports
+      .iter()
+      .filter(|(local, import)| {
+        import.kind == ImportKind::Import
+          && &*import.specifier != "*"
+          && !self.used_imports.contains(*local)
+          && !self.exports_locals.contains_key(*local)
+      })
+      .map(|(local, import)| CollectImportedSymbol {
+        source: import.source.clone(),
+        local: local.0.clone(),
+        imported: import.specifier.clone(),
+        loc: import.loc.clone(),
+        kind: import.kind,
+      })
+      .collect();
+
+    unused_imports.sort_by_key(|import| (import.loc.start_line, import.loc.start_col));
+    unused_imports
+  }
+
   pub fn match_require(&self, node: &Expr) -> Option<JsWord> {
     match_require(node, self.unresolved_mark, self.ignore_mark)
   }
@@ -1134,6 +1180,23 @@ impl Collect {
     }
   }
 
+  /// Record an export, noting ES module exports that reuse the name of an earlier one
+  ///
+  /// CommonJS exports may be assigned any number of times, so they are never duplicates.
+  fn insert_export(&mut self, exported: JsWord, export: Export) {
+    if export.is_esm {
+      if let Some(first) = self.exports.get(&exported).filter(|first| first.is_esm) {
+        self.duplicate_exports.push(CollectDuplicateExport {
+          exported: exported.clone(),
+          first: first.loc.clone(),
+          loc: export.loc.clone(),
+        });
+      }
+    }
+
+    self.exports.insert(exported, export);
+  }
+
   fn add_bailout(&mut self, span: Span, reason: BailoutReason) {
     if let Some(bailouts) = &mut self.bailouts {
       bailouts.push(Bailout {
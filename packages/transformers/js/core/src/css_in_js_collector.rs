@@ -0,0 +1,73 @@
+use swc_core::common::sync::Lrc;
+use swc_core::common::SourceMap;
+use swc_core::common::SourceMapper;
+use swc_core::ecma::ast::Callee;
+use swc_core::ecma::ast::Expr;
+use swc_core::ecma::ast::TaggedTpl;
+use swc_core::ecma::visit::Visit;
+use swc_core::ecma::visit::VisitWith;
+
+use crate::utils::SourceLocation;
+
+/// Collects the CSS of tagged template literals used by CSS-in-JS libraries
+///
+/// This matches the `css`, `keyframes`, `createGlobalStyle` and `injectGlobal` tags, as well as
+/// `styled` tags such as `styled.div`, `styled(Button)` and `styled.div.attrs(...)`. Tags are
+/// matched by name, so this does not check where they are imported from.
+///
+/// The CSS is collected as written, so interpolations such as `${color}` are kept as they are.
+pub struct CssInJsCollector<'a> {
+  templates: &'a mut Vec<(String, SourceLocation)>,
+  source_map: Lrc<SourceMap>,
+}
+
+impl<'a> CssInJsCollector<'a> {
+  pub fn new(templates: &'a mut Vec<(String, SourceLocation)>, source_map: Lrc<SourceMap>) -> Self {
+    Self {
+      templates,
+      source_map,
+    }
+  }
+}
+
+fn is_css_tag(tag: &Expr) -> bool {
+  match tag {
+    Expr::Ident(ident) => matches!(
+      &*ident.sym,
+      "css" | "keyframes" | "createGlobalStyle" | "injectGlobal"
+    ),
+    _ => is_styled(tag),
+  }
+}
+
+fn is_styled(expr: &Expr) -> bool {
+  match expr {
+    Expr::Ident(ident) => &*ident.sym == "styled",
+    Expr::Member(member) => is_styled(&member.obj),
+    Expr::Call(call) => match &call.callee {
+      Callee::Expr(callee) => is_styled(callee),
+      _ => false,
+    },
+    _ => false,
+  }
+}
+
+impl Visit for CssInJsCollector<'_> {
+  fn visit_tagged_tpl(&mut self, node: &TaggedTpl) {
+    if is_css_tag(&node.tag) {
+      if let Ok(snippet) = self.source_map.span_to_snippet(node.tpl.span) {
+        let css = snippet
+          .strip_prefix('`')
+          .and_then(|css| css.strip_suffix('`'))
+          .unwrap_or(&snippet);
+
+        self.templates.push((
+          css.to_string(),
+          SourceLocation::from(&self.source_map, node.tpl.span),
+        ));
+      }
+    }
+
+    node.visit_children_with(self);
+  }
+}
@@ -1,8 +1,5 @@
-use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::fmt;
-use std::hash::Hash;
-use std::hash::Hasher;
 use std::path::Path;
 
 use path_slash::PathBufExt;
@@ -23,17 +20,10 @@ use swc_core::ecma::visit::Fold;
 use swc_core::ecma::visit::FoldWith;
 
 use crate::fold_member_expr_skip_prop;
+use crate::hash;
 use crate::utils::*;
 use crate::Config;
 
-macro_rules! hash {
-  ($str:expr) => {{
-    let mut hasher = DefaultHasher::new();
-    $str.hash(&mut hasher);
-    hasher.finish()
-  }};
-}
-
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum DependencyKind {
   /// Corresponds to ESM import statements
@@ -115,8 +105,18 @@ pub struct DependencyDescriptor {
   /// The text specifier associated with the import/export statement.
   pub specifier: swc_core::ecma::atoms::JsWord,
   pub attributes: Option<HashMap<swc_core::ecma::atoms::JsWord, bool>>,
+  /// Import attributes from the `with` (or legacy `assert`) option of a dynamic import, such as
+  /// `type: 'json'`
+  pub import_attributes: Option<HashMap<JsWord, JsWord>>,
   pub is_optional: bool,
   pub is_helper: bool,
+  /// Whether this is a dynamic import whose result is awaited directly, as in `await import('x')`
+  pub is_awaited: bool,
+  /// Whether the dependency is only resolved to a URL, as in `import.meta.resolve('x')`
+  pub is_resolve_only: bool,
+  /// Whether the specifier is a glob for the set of modules a template literal dynamic import may
+  /// load, as in ``import(`./locales/${lang}.js`)``
+  pub is_context: bool,
   pub source_type: Option<SourceType>,
   pub placeholder: Option<String>,
 }
@@ -134,6 +134,7 @@ pub fn dependency_collector<'a>(
     source_map,
     items,
     in_try: false,
+    in_await: false,
     in_promise: false,
     require_node: None,
     ignore_mark,
@@ -148,6 +149,7 @@ struct DependencyCollector<'a> {
   source_map: Lrc<SourceMap>,
   items: &'a mut Vec<DependencyDescriptor>,
   in_try: bool,
+  in_await: bool,
   in_promise: bool,
   require_node: Option<ast::CallExpr>,
   ignore_mark: swc_core::common::Mark,
@@ -205,8 +207,12 @@ impl<'a> DependencyCollector<'a> {
       loc: SourceLocation::from(&self.source_map, span),
       specifier,
       attributes,
+      import_attributes: None,
       is_optional,
       is_helper: span.is_dummy(),
+      is_awaited: false,
+      is_resolve_only: false,
+      is_context: false,
       source_type: Some(source_type),
       placeholder: placeholder.clone(),
     });
@@ -252,8 +258,12 @@ impl<'a> DependencyCollector<'a> {
       loc: SourceLocation::from(&self.source_map, span),
       specifier,
       attributes: None,
+      import_attributes: None,
       is_optional: false,
       is_helper: span.is_dummy(),
+      is_awaited: false,
+      is_resolve_only: false,
+      is_context: false,
       source_type: Some(source_type),
       placeholder: Some(placeholder.clone()),
     });
@@ -298,11 +308,48 @@ impl<'a> DependencyCollector<'a> {
       )),
     });
   }
+
+  fn add_computed_require_warning(&mut self, span: Span) {
+    self.diagnostics.push(Diagnostic {
+      message: "Computed `require` calls cannot be statically resolved.".to_string(),
+      code_highlights: Some(vec![CodeHighlight {
+        message: None,
+        loc: SourceLocation::from(&self.source_map, span),
+      }]),
+      hints: Some(vec![String::from(
+        "Pass a string literal to `require` so that the dependency can be bundled.",
+      )]),
+      show_environment: false,
+      severity: DiagnosticSeverity::Warning,
+      documentation_url: None,
+    });
+  }
+
+  /// Computed dynamic imports are reported as errors when `strict_dynamic_imports` is enabled
+  fn add_computed_dynamic_import_diagnostic(&mut self, span: Span) {
+    self.diagnostics.push(Diagnostic {
+      message: "Computed dynamic imports cannot be statically resolved.".to_string(),
+      code_highlights: Some(vec![CodeHighlight {
+        message: None,
+        loc: SourceLocation::from(&self.source_map, span),
+      }]),
+      hints: Some(vec![String::from(
+        "Pass a string literal to `import()` so that the dependency can be bundled.",
+      )]),
+      show_environment: false,
+      severity: if self.config.strict_dynamic_imports {
+        DiagnosticSeverity::Error
+      } else {
+        DiagnosticSeverity::Warning
+      },
+      documentation_url: None,
+    });
+  }
 }
 
 fn rewrite_require_specifier(node: ast::CallExpr, unresolved_mark: Mark) -> ast::CallExpr {
   if let Some(arg) = node.args.first() {
-    if let Some((value, _)) = match_str(&arg.expr) {
+    if let Some((value, _)) = match_specifier(&arg.expr) {
       if value.starts_with("node:") {
         // create_require will take care of replacing the node: prefix...
         return create_require(value, unresolved_mark);
@@ -312,6 +359,31 @@ fn rewrite_require_specifier(node: ast::CallExpr, unresolved_mark: Mark) -> ast:
   node
 }
 
+/// Match the string specifier of a `require` or `import` call
+///
+/// Unlike [`match_str`], concatenated string literals such as `'pkg' + '/sub'` are folded, so
+/// that they are treated as static dependencies.
+fn match_specifier(node: &ast::Expr) -> Option<(JsWord, Span)> {
+  match node {
+    ast::Expr::Bin(ast::BinExpr {
+      op: ast::BinaryOp::Add,
+      left,
+      right,
+      span,
+    }) => {
+      let (left, _) = match_specifier(left)?;
+      let (right, _) = match_specifier(right)?;
+      Some((format!("{}{}", left, right).into(), *span))
+    }
+    _ => match_str(node),
+  }
+}
+
+/// Whether an import was injected by SWC for a helper, rather than written in the source code
+fn is_injected_helper_import(import: &ast::ImportDecl) -> bool {
+  import.span.is_dummy() && import.src.value.starts_with("@swc/helpers/")
+}
+
 impl<'a> Fold for DependencyCollector<'a> {
   fn fold_module(&mut self, node: ast::Module) -> ast::Module {
     let mut res = node.fold_children_with(self);
@@ -328,6 +400,11 @@ impl<'a> Fold for DependencyCollector<'a> {
     // If an import or export is seen within a script, flag it to throw an error from JS.
     if self.config.source_type == SourceType::Script {
       match node {
+        // SWC helpers are imported rather than inlined when `external_helpers` is set. These
+        // imports are generated, so they are collected as in modules.
+        ast::ModuleDecl::Import(ref import) if is_injected_helper_import(import) => {
+          return node.fold_children_with(self);
+        }
         ast::ModuleDecl::Import(ast::ImportDecl { span, .. })
         | ast::ModuleDecl::ExportAll(ast::ExportAll { span, .. })
         | ast::ModuleDecl::ExportDecl(ast::ExportDecl { span, .. })
@@ -422,9 +499,37 @@ impl<'a> Fold for DependencyCollector<'a> {
     }
   }
 
+  fn fold_await_expr(&mut self, node: ast::AwaitExpr) -> ast::AwaitExpr {
+    // Track dynamic imports that are awaited directly, since any others may be preloaded
+    self.in_await = matches!(
+      &*node.arg,
+      ast::Expr::Call(ast::CallExpr {
+        callee: Callee::Import(_),
+        ..
+      })
+    );
+
+    node.fold_children_with(self)
+  }
+
   fn fold_call_expr(&mut self, node: ast::CallExpr) -> ast::CallExpr {
     use ast::Expr::*;
     use ast::Ident;
+    let is_awaited = std::mem::take(&mut self.in_await);
+
+    if self.config.follow_fetch_urls {
+      if let Some((specifier, span)) = self.match_fetch_url(&node) {
+        let url = self.add_url_dependency(
+          specifier,
+          span,
+          DependencyKind::Url,
+          self.config.source_type,
+        );
+        let mut node = node.fold_children_with(self);
+        *node.args[0].expr = url;
+        return node;
+      }
+    }
 
     let kind = match &node.callee {
       Callee::Import(_) => DependencyKind::DynamicImport,
@@ -614,6 +719,7 @@ impl<'a> Fold for DependencyCollector<'a> {
 
     // Convert import attributes for dynamic import
     let mut attributes = None;
+    let mut import_attributes = None;
     if kind == DependencyKind::DynamicImport {
       if let Some(arg) = node.args.get(1) {
         if let Object(arg) = &*arg.expr {
@@ -635,6 +741,14 @@ impl<'a> Fold for DependencyCollector<'a> {
               _ => continue,
             };
 
+            // import('./data.json', { with: { type: 'json' } })
+            if &*k == "with" || &*k == "assert" {
+              if let Object(object) = &*kv.value {
+                import_attributes = Some(match_import_attributes(object));
+              }
+              continue;
+            }
+
             let v = match &*kv.value {
               Lit(ast::Lit::Bool(ast::Bool { value, .. })) => *value,
               _ => continue,
@@ -704,7 +818,7 @@ impl<'a> Fold for DependencyCollector<'a> {
         return node;
       }
 
-      if let Some((specifier, span)) = match_str(&arg.expr) {
+      if let Some((specifier, span)) = match_specifier(&arg.expr) {
         // require() calls aren't allowed in scripts, flag as an error.
         if kind == DependencyKind::Require && self.config.source_type == SourceType::Script {
           self.add_script_error(node.span);
@@ -720,6 +834,18 @@ impl<'a> Fold for DependencyCollector<'a> {
           self.config.source_type,
         );
 
+        if is_awaited {
+          if let Some(dependency) = self.items.last_mut() {
+            dependency.is_awaited = true;
+          }
+        }
+
+        if import_attributes.is_some() {
+          if let Some(dependency) = self.items.last_mut() {
+            dependency.import_attributes = import_attributes;
+          }
+        }
+
         if let Some(placeholder) = placeholder {
           let mut node = node.clone();
           node.args[0].expr = Box::new(ast::Expr::Lit(ast::Lit::Str(ast::Str {
@@ -731,7 +857,33 @@ impl<'a> Fold for DependencyCollector<'a> {
         } else {
           node
         }
+      } else if let Some((specifier, span)) = self.match_context_import(&kind, &arg.expr) {
+        self.add_dependency(
+          specifier,
+          span,
+          kind.clone(),
+          attributes,
+          false,
+          self.config.source_type,
+        );
+
+        // The specifier is a glob that the code does not refer to, so there is no placeholder
+        if let Some(dependency) = self.items.last_mut() {
+          dependency.is_awaited = is_awaited;
+          dependency.is_context = true;
+          dependency.import_attributes = import_attributes;
+          dependency.placeholder = None;
+        }
+
+        node
       } else {
+        // Optional requires within try blocks are usually intentional, so these are not reported.
+        if kind == DependencyKind::Require && !self.in_try {
+          self.add_computed_require_warning(node.span);
+        } else if kind == DependencyKind::DynamicImport {
+          self.add_computed_dynamic_import_diagnostic(node.span);
+        }
+
         node
       }
     } else {
@@ -891,6 +1043,30 @@ impl<'a> Fold for DependencyCollector<'a> {
   fn fold_expr(&mut self, node: ast::Expr) -> ast::Expr {
     use ast::*;
 
+    if let Some((specifier, span)) = self.match_import_meta_resolve(&node) {
+      let url = self.add_url_dependency(
+        specifier,
+        span,
+        DependencyKind::Url,
+        self.config.source_type,
+      );
+      if let Some(dependency) = self.items.last_mut() {
+        dependency.is_resolve_only = true;
+      }
+
+      // `import.meta.resolve` returns a string, so take the href of the URL object of libraries.
+      // Otherwise, the JSRuntime already returns a string.
+      if self.config.is_library || self.config.standalone {
+        return Expr::Member(MemberExpr {
+          span: DUMMY_SP,
+          obj: Box::new(url),
+          prop: MemberProp::Ident(Ident::new("href".into(), DUMMY_SP)),
+        });
+      }
+
+      return url;
+    }
+
     if self.is_import_meta(&node) {
       return self.get_import_meta();
     }
@@ -1208,6 +1384,86 @@ impl Fold for PromiseTransformer {
 }
 
 impl<'a> DependencyCollector<'a> {
+  /// Match `fetch('./data.json')` calls with a relative string literal URL
+  fn match_fetch_url(&self, call: &ast::CallExpr) -> Option<(JsWord, swc_core::common::Span)> {
+    let Callee::Expr(callee) = &call.callee else {
+      return None;
+    };
+    let ast::Expr::Ident(ident) = &**callee else {
+      return None;
+    };
+
+    if &*ident.sym != "fetch" || !is_unresolved(ident, self.unresolved_mark) {
+      return None;
+    }
+
+    let (specifier, span) = match_str(&call.args.first()?.expr)?;
+    if specifier.starts_with("./") || specifier.starts_with("../") {
+      Some((specifier, span))
+    } else {
+      None
+    }
+  }
+
+  /// Match a dynamic import of a relative template literal, such as `./locales/${lang}.js`, as a
+  /// glob of the modules it may load, such as `./locales/*.js`
+  ///
+  /// This is only done when `context_dynamic_imports` is enabled. Each expression is replaced with
+  /// a `*`, which does not match across directories.
+  fn match_context_import(
+    &self,
+    kind: &DependencyKind,
+    expr: &ast::Expr,
+  ) -> Option<(JsWord, swc_core::common::Span)> {
+    if !self.config.context_dynamic_imports || *kind != DependencyKind::DynamicImport {
+      return None;
+    }
+
+    let ast::Expr::Tpl(tpl) = expr else {
+      return None;
+    };
+
+    let prefix = tpl.quasis.first()?.raw.as_ref();
+    if tpl.exprs.is_empty() || !(prefix.starts_with("./") || prefix.starts_with("../")) {
+      return None;
+    }
+
+    let mut glob = String::new();
+    for (index, quasi) in tpl.quasis.iter().enumerate() {
+      if index > 0 && !glob.ends_with('*') {
+        glob.push('*');
+      }
+      glob.push_str(&quasi.raw);
+    }
+
+    Some((glob.into(), tpl.span))
+  }
+
+  /// Match `import.meta.resolve('x')` with a string literal specifier
+  fn match_import_meta_resolve(
+    &mut self,
+    expr: &ast::Expr,
+  ) -> Option<(JsWord, swc_core::common::Span)> {
+    use ast::*;
+
+    let Expr::Call(call) = expr else {
+      return None;
+    };
+    let Callee::Expr(callee) = &call.callee else {
+      return None;
+    };
+    let Expr::Member(member) = &**callee else {
+      return None;
+    };
+
+    let is_resolve = matches!(&member.prop, MemberProp::Ident(prop) if &*prop.sym == "resolve");
+    if !is_resolve || !self.is_import_meta(&member.obj) {
+      return None;
+    }
+
+    match_str(&call.args.first()?.expr)
+  }
+
   fn match_new_url(&mut self, expr: &ast::Expr) -> Option<(JsWord, swc_core::common::Span)> {
     use ast::*;
 
@@ -1404,6 +1660,31 @@ impl<'a> DependencyCollector<'a> {
 }
 
 // matches the `type: 'module'` option of workers
+/// Collects the string valued attributes of a dynamic import's `with` option
+fn match_import_attributes(object: &ast::ObjectLit) -> HashMap<JsWord, JsWord> {
+  let mut attributes = HashMap::new();
+  for prop in &object.props {
+    let ast::PropOrSpread::Prop(prop) = prop else {
+      continue;
+    };
+    let ast::Prop::KeyValue(kv) = &**prop else {
+      continue;
+    };
+
+    let key = match &kv.key {
+      ast::PropName::Ident(ast::Ident { sym, .. })
+      | ast::PropName::Str(ast::Str { value: sym, .. }) => sym.clone(),
+      _ => continue,
+    };
+
+    if let ast::Expr::Lit(ast::Lit::Str(ast::Str { value, .. })) = &*kv.value {
+      attributes.insert(key, value.clone());
+    }
+  }
+
+  attributes
+}
+
 fn match_worker_type(expr: Option<&ast::ExprOrSpread>) -> (SourceType, Option<ast::ExprOrSpread>) {
   use ast::*;
 
@@ -1484,6 +1765,7 @@ mod test {
       source_map: context.source_map.clone(),
       items,
       in_try: false,
+      in_await: false,
       in_promise: false,
       require_node: None,
       ignore_mark: Mark::new(),
@@ -1507,6 +1789,86 @@ mod test {
     )
   }
 
+  #[test]
+  fn test_awaited_dynamic_import_dependency() {
+    let mut items = vec![];
+    let mut diagnostics = vec![];
+    let config = Config::default();
+    let input_code = r#"
+      const { x } = await import('awaited');
+      const p = import('stored');
+    "#;
+
+    run_fold(input_code, |context| {
+      make_dependency_collector(context, &mut items, &mut diagnostics, &config)
+    });
+
+    let is_awaited = items
+      .iter()
+      .map(|item| (item.specifier.as_ref(), item.is_awaited))
+      .collect::<Vec<(&str, bool)>>();
+
+    assert_eq!(is_awaited, vec![("awaited", true), ("stored", false)]);
+  }
+
+  #[test]
+  fn test_context_dynamic_import_dependency() {
+    let input_code = r#"
+      const locale = import(`./locales/${lang}.js`);
+      const other = import(`${base}/other.js`);
+    "#;
+
+    let collect = |context_dynamic_imports: bool| {
+      let mut items = vec![];
+      let mut diagnostics = vec![];
+      let config = Config {
+        context_dynamic_imports,
+        ..Config::default()
+      };
+
+      run_fold(input_code, |context| {
+        make_dependency_collector(context, &mut items, &mut diagnostics, &config)
+      });
+
+      (items, diagnostics.len())
+    };
+
+    let (items, diagnostic_count) = collect(true);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].kind, DependencyKind::DynamicImport);
+    assert_eq!(items[0].specifier, JsWord::from("./locales/*.js"));
+    assert!(items[0].is_context);
+    assert_eq!(items[0].placeholder, None);
+    // The import of a non-relative template is still reported as computed
+    assert_eq!(diagnostic_count, 1);
+
+    let (items, diagnostic_count) = collect(false);
+    assert!(items.is_empty());
+    assert_eq!(diagnostic_count, 2);
+  }
+
+  #[test]
+  fn test_import_meta_resolve_dependency() {
+    let mut items = vec![];
+    let mut diagnostics = vec![];
+    let config = Config::default();
+    let input_code = r#"
+const url = import.meta.resolve('./m');
+    "#;
+
+    let RunVisitResult { output_code, .. } = run_fold(input_code, |context| {
+      make_dependency_collector(context, &mut items, &mut diagnostics, &config)
+    });
+
+    let hash = make_placeholder_hash("./m", DependencyKind::Url);
+    assert_eq!(output_code, format!("const url = require(\"{}\");\n", hash));
+    assert_eq!(diagnostics, []);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].kind, DependencyKind::Url);
+    assert_eq!(items[0].specifier, JsWord::from("./m"));
+    assert!(items[0].is_resolve_only);
+  }
+
   #[test]
   fn test_dynamic_import_dependency() {
     let mut items = vec![];
@@ -1742,6 +2104,87 @@ try {{
     );
   }
 
+  #[test]
+  fn test_computed_require_warning() {
+    let mut items = vec![];
+    let mut diagnostics = vec![];
+    let config = make_config();
+    let input_code = r#"
+const x = 'other';
+const { y } = require(x);
+    "#;
+
+    let RunVisitResult { output_code, .. } = run_fold(input_code, |context| {
+      make_dependency_collector(context, &mut items, &mut diagnostics, &config)
+    });
+
+    assert_eq!(output_code, input_code.trim_start().trim_end_matches(' '));
+    assert_eq!(items, []);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+    assert_eq!(
+      diagnostics[0].message,
+      "Computed `require` calls cannot be statically resolved."
+    );
+
+    let loc = &diagnostics[0].code_highlights.as_ref().unwrap()[0].loc;
+    assert_eq!(
+      (loc.start_line, loc.start_col, loc.end_line, loc.end_col),
+      (3, 15, 3, 25)
+    );
+  }
+
+  #[test]
+  fn test_computed_dynamic_import_diagnostic() {
+    let input_code = r#"
+const x = 'other';
+import(x);
+    "#;
+
+    for (strict_dynamic_imports, severity) in [
+      (false, DiagnosticSeverity::Warning),
+      (true, DiagnosticSeverity::Error),
+    ] {
+      let mut items = vec![];
+      let mut diagnostics = vec![];
+      let config = Config {
+        strict_dynamic_imports,
+        ..make_config()
+      };
+
+      run_fold(input_code, |context| {
+        make_dependency_collector(context, &mut items, &mut diagnostics, &config)
+      });
+
+      assert_eq!(items, []);
+      assert_eq!(diagnostics.len(), 1);
+      assert_eq!(diagnostics[0].severity, severity);
+      assert_eq!(
+        diagnostics[0].message,
+        "Computed dynamic imports cannot be statically resolved."
+      );
+    }
+  }
+
+  #[test]
+  fn test_computed_optional_require_is_not_reported() {
+    let mut items = vec![];
+    let mut diagnostics = vec![];
+    let config = make_config();
+    let input_code = r#"
+try {
+  require(x);
+} catch (err) {}
+    "#;
+
+    run_fold(input_code, |context| {
+      make_dependency_collector(context, &mut items, &mut diagnostics, &config)
+    });
+
+    assert_eq!(items, []);
+    assert_eq!(diagnostics, []);
+  }
+
   // Require is treated as dynamic import
   #[test]
   fn test_compiled_dynamic_imports() {
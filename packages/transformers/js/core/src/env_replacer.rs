@@ -61,19 +61,23 @@ impl<'a> VisitMut for EnvReplacer<'a> {
       }
 
       if !self.replace_env {
+        // process.env.NODE_ENV is always inlined, since libraries rely on it for dead-code elimination
+        if let Some(replacement) = self.replace_node_env(member) {
+          *node = replacement;
+          return;
+        }
+
         node.visit_mut_children_with(self);
         return;
       }
 
-      if let Expr::Member(obj) = &*member.obj {
-        if match_member_expr(obj, vec!["process", "env"], self.unresolved_mark) {
-          if let Some((sym, _)) = match_property_name(member) {
-            if let Some(replacement) = self.replace(&sym, true) {
-              *node = replacement;
-              return;
-            }
-          }
+      if let Some((sym, replacement)) = self.replace_env_member(member) {
+        if self.is_browser && !self.env.contains_key(&sym) {
+          self.emit_undefined_env_warning(&sym, member.span);
         }
+
+        *node = replacement;
+        return;
       }
     }
 
@@ -156,10 +160,21 @@ impl<'a> VisitMut for EnvReplacer<'a> {
                 *node = match &node {
                   Expr::Unary(_) => Expr::Lit(Lit::Bool(Bool { span: *span, value: true })),
                   Expr::Update(_) => {
-                    // TODO: This can be written to run in-place to make it more efficient
-                    let mut replacement = *arg.clone();
-                    replacement.visit_mut_with(self);
-                    replacement
+                    // The mutation has already been reported, so this does not warn about the
+                    // variable being undefined as well
+                    let target = match &**arg {
+                      Expr::Member(target) => self.replace_env_member(target),
+                      _ => None,
+                    };
+
+                    if let Some((_, replacement)) = target {
+                      replacement
+                    } else {
+                      // TODO: This can be written to run in-place to make it more efficient
+                      let mut replacement = *arg.clone();
+                      replacement.visit_mut_with(self);
+                      replacement
+                    }
                   }
                   _ => unreachable!()
                 };
@@ -232,6 +247,38 @@ impl<'a> EnvReplacer<'a> {
     Some(Expr::Assign(res))
   }
 
+  /// Replaces `process.env.NODE_ENV` with its value, if it is set in the environment
+  fn replace_node_env(&mut self, member: &MemberExpr) -> Option<Expr> {
+    let Expr::Member(obj) = &*member.obj else {
+      return None;
+    };
+
+    if !match_member_expr(obj, vec!["process", "env"], self.unresolved_mark) {
+      return None;
+    }
+
+    match match_property_name(member) {
+      Some((sym, _)) if &*sym == "NODE_ENV" => self.replace(&sym, false),
+      _ => None,
+    }
+  }
+
+  /// Replaces `process.env.SOMETHING` with its value, or `undefined` if it is not set
+  fn replace_env_member(&mut self, member: &MemberExpr) -> Option<(JsWord, Expr)> {
+    let Expr::Member(obj) = &*member.obj else {
+      return None;
+    };
+
+    if !match_member_expr(obj, vec!["process", "env"], self.unresolved_mark) {
+      return None;
+    }
+
+    let (sym, _) = match_property_name(member)?;
+    let replacement = self.replace(&sym, true)?;
+
+    Some((sym, replacement))
+  }
+
   fn replace(&mut self, sym: &JsWord, fallback_undefined: bool) -> Option<Expr> {
     if let Some(val) = self.env.get(sym) {
       self.used_env.insert(sym.clone());
@@ -347,6 +394,26 @@ impl<'a> EnvReplacer<'a> {
       documentation_url: None,
     });
   }
+
+  fn emit_undefined_env_warning(&mut self, sym: &JsWord, span: swc_core::common::Span) {
+    self.diagnostics.push(Diagnostic {
+      message: format!(
+        "process.env.{} is not defined, so it is replaced with undefined",
+        sym
+      ),
+      code_highlights: Some(vec![CodeHighlight {
+        message: None,
+        loc: SourceLocation::from(&self.source_map, span),
+      }]),
+      hints: Some(vec![format!(
+        "Set {} in the environment or a .env file if this build relies on it",
+        sym
+      )]),
+      show_environment: true,
+      severity: DiagnosticSeverity::Warning,
+      documentation_url: None,
+    });
+  }
 }
 
 #[cfg(test)]
@@ -407,6 +474,38 @@ const { package, IS_TEST: isTest2, ...other } = process.env;
     );
   }
 
+  #[test]
+  fn test_replacer_disabled_still_replaces_node_env() {
+    let mut env: HashMap<JsWord, JsWord> = HashMap::new();
+    env.insert("NODE_ENV".into(), "production".into());
+    env.insert("IS_TEST".into(), "true".into());
+    let mut used_env = HashSet::new();
+    let mut diagnostics = Vec::new();
+
+    let RunVisitResult { output_code, .. } = run_visit(
+      r#"const mode = process.env.NODE_ENV;
+const isTest = process.env.IS_TEST;
+"#,
+      |run_test_context: RunTestContext| EnvReplacer {
+        replace_env: false,
+        is_browser: true,
+        env: &env,
+        used_env: &mut used_env,
+        source_map: run_test_context.source_map.clone(),
+        diagnostics: &mut diagnostics,
+        unresolved_mark: run_test_context.unresolved_mark,
+      },
+    );
+
+    assert_eq!(
+      output_code,
+      r#"const mode = "production";
+const isTest = process.env.IS_TEST;
+"#,
+    );
+    assert_eq!(used_env, HashSet::from(["NODE_ENV".into()]));
+  }
+
   // TODO: This behaviour should be removed and will be disabled for canary builds.
   #[test]
   fn test_replace_browser_assignments() {
@@ -508,6 +607,34 @@ undefined;
     );
   }
 
+  #[test]
+  fn test_warn_about_undefined_env_in_browser() {
+    let env: HashMap<JsWord, JsWord> = HashMap::new();
+    let mut used_env = HashSet::new();
+    let mut diagnostics = Vec::new();
+
+    let RunVisitResult { output_code, .. } = run_visit(
+      r#"
+const value = process.env.UNKNOWN;
+    "#,
+      |run_test_context: RunTestContext| {
+        make_env_replacer(run_test_context, &env, &mut used_env, &mut diagnostics)
+      },
+    );
+
+    assert_eq!(
+      output_code,
+      r#"const value = undefined;
+"#
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+      diagnostics[0].message,
+      "process.env.UNKNOWN is not defined, so it is replaced with undefined"
+    );
+    assert_eq!(diagnostics[0].severity, DiagnosticSeverity::Warning);
+  }
+
   #[test]
   fn test_replace_env_in_expressions() {
     let mut env: HashMap<JsWord, JsWord> = HashMap::new();
@@ -0,0 +1,104 @@
+use swc_core::common::Mark;
+use swc_core::ecma::ast::Expr;
+use swc_core::ecma::ast::Lit;
+use swc_core::ecma::ast::Str;
+use swc_core::ecma::ast::UnaryOp;
+use swc_core::ecma::atoms::js_word;
+use swc_core::ecma::visit::{VisitMut, VisitMutWith};
+
+use crate::utils::is_unresolved;
+
+/// Replaces `typeof window` with the string literal it evaluates to in the target environment
+///
+/// `window` is an object in browsers, and is undefined in workers and in Node. Replacing it lets
+/// the expression simplifier fold feature checks such as `typeof window !== 'undefined'`, so that
+/// the branches for other environments are removed as dead code.
+///
+/// As with `TypeofReplacer`, only the global `window` is replaced, not variables that shadow it.
+pub struct EnvironmentCheckReplacer {
+  unresolved_mark: Mark,
+  has_window: bool,
+}
+
+impl EnvironmentCheckReplacer {
+  pub fn new(unresolved_mark: Mark, is_browser: bool, is_worker: bool) -> Self {
+    Self {
+      unresolved_mark,
+      has_window: is_browser && !is_worker,
+    }
+  }
+
+  fn get_replacement(&self, node: &Expr) -> Option<Expr> {
+    let Expr::Unary(unary) = node else {
+      return None;
+    };
+    if unary.op != UnaryOp::TypeOf {
+      return None;
+    }
+    let Expr::Ident(ident) = &*unary.arg else {
+      return None;
+    };
+    if &*ident.sym != "window" || !is_unresolved(ident, self.unresolved_mark) {
+      return None;
+    }
+
+    Some(Expr::Lit(Lit::Str(Str {
+      span: unary.span,
+      value: if self.has_window {
+        js_word!("object")
+      } else {
+        js_word!("undefined")
+      },
+      raw: None,
+    })))
+  }
+}
+
+impl VisitMut for EnvironmentCheckReplacer {
+  fn visit_mut_expr(&mut self, node: &mut Expr) {
+    let Some(replacement) = self.get_replacement(node) else {
+      node.visit_mut_children_with(self);
+      return;
+    };
+
+    *node = replacement;
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use crate::test_utils::run_visit;
+
+  use super::*;
+
+  #[test]
+  fn test_environment_check_replacer() {
+    let code = r#"
+const a = typeof window;
+function wrapper(window) {
+    const b = typeof window;
+}
+"#;
+
+    let browser_code = run_visit(code, |context| {
+      EnvironmentCheckReplacer::new(context.unresolved_mark, true, false)
+    })
+    .output_code;
+    assert_eq!(
+      browser_code,
+      r#"
+const a = "object";
+function wrapper(window) {
+    const b = typeof window;
+}
+"#
+      .trim_start()
+    );
+
+    let worker_code = run_visit(code, |context| {
+      EnvironmentCheckReplacer::new(context.unresolved_mark, true, true)
+    })
+    .output_code;
+    assert!(worker_code.starts_with("const a = \"undefined\";"));
+  }
+}
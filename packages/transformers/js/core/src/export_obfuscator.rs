@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use swc_core::common::DUMMY_SP;
+use swc_core::ecma::ast::*;
+use swc_core::ecma::atoms::JsWord;
+use swc_core::ecma::visit::Fold;
+use swc_core::ecma::visit::FoldWith;
+
+use crate::fold_member_expr_skip_prop;
+use crate::hash;
+use crate::id;
+use crate::utils::match_export_name;
+
+/// Renames the local variables of named exports that are not part of the public API
+///
+/// Libraries can use this to hide the names of their internal helpers, which would otherwise be
+/// kept in the output as the names of local variables. Renamed variables are named after a hash of
+/// the module id and their original name, while the exported names are left alone so that imports
+/// keep working.
+///
+/// Exports listed in `keep` are not renamed. Default exports and destructured declarations, such
+/// as `export const { a } = b`, are not renamed either.
+pub struct ExportObfuscator<'a> {
+  keep: &'a [String],
+  module_id: &'a str,
+  renames: HashMap<Id, JsWord>,
+}
+
+impl<'a> ExportObfuscator<'a> {
+  pub fn new(keep: &'a [String], module_id: &'a str) -> Self {
+    Self {
+      keep,
+      module_id,
+      renames: HashMap::new(),
+    }
+  }
+
+  fn is_kept(&self, exported: &JsWord) -> bool {
+    self.keep.iter().any(|name| name.as_str() == &**exported)
+  }
+
+  fn obfuscated_name(&self, local: &JsWord) -> JsWord {
+    format!("${:x}", hash!(format!("{}:{}", self.module_id, local))).into()
+  }
+
+  fn rename(&mut self, ident: &Ident) {
+    let name = self.obfuscated_name(&ident.sym);
+    self.renames.entry(id!(ident)).or_insert(name);
+  }
+
+  /// Split `export function foo() {}` into a declaration and an `export { foo }`, so that the
+  /// declaration can be renamed without changing the exported name
+  fn split_export_decl(&mut self, export: ExportDecl) -> Vec<ModuleItem> {
+    let idents: Vec<Ident> = match &export.decl {
+      Decl::Class(class) => vec![class.ident.clone()],
+      Decl::Fn(func) => vec![func.ident.clone()],
+      Decl::Var(var) => {
+        let idents: Vec<Ident> = var
+          .decls
+          .iter()
+          .filter_map(|decl| match &decl.name {
+            Pat::Ident(binding) => Some(binding.id.clone()),
+            _ => None,
+          })
+          .collect();
+
+        if idents.len() != var.decls.len() {
+          vec![]
+        } else {
+          idents
+        }
+      }
+      _ => vec![],
+    };
+
+    if idents.iter().all(|ident| self.is_kept(&ident.sym)) {
+      return vec![ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export))];
+    }
+
+    let specifiers = idents
+      .iter()
+      .map(|ident| {
+        if !self.is_kept(&ident.sym) {
+          self.rename(ident);
+        }
+
+        ExportSpecifier::Named(ExportNamedSpecifier {
+          span: DUMMY_SP,
+          orig: ModuleExportName::Ident(ident.clone()),
+          exported: None,
+          is_type_only: false,
+        })
+      })
+      .collect();
+
+    vec![
+      ModuleItem::Stmt(Stmt::Decl(export.decl)),
+      ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+        span: export.span,
+        specifiers,
+        src: None,
+        type_only: false,
+        with: None,
+      })),
+    ]
+  }
+}
+
+impl Fold for ExportObfuscator<'_> {
+  fold_member_expr_skip_prop! {}
+
+  fn fold_module(&mut self, node: Module) -> Module {
+    let mut body = Vec::with_capacity(node.body.len());
+
+    for item in node.body {
+      match item {
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => {
+          body.extend(self.split_export_decl(export));
+        }
+        ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) if export.src.is_none() => {
+          for specifier in &export.specifiers {
+            if let ExportSpecifier::Named(named) = specifier {
+              if let ModuleExportName::Ident(orig) = &named.orig {
+                let exported = match &named.exported {
+                  Some(exported) => match_export_name(exported).0,
+                  None => orig.sym.clone(),
+                };
+
+                if !self.is_kept(&exported) {
+                  self.rename(orig);
+                }
+              }
+            }
+          }
+
+          body.push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)));
+        }
+        item => body.push(item),
+      }
+    }
+
+    if self.renames.is_empty() {
+      return Module { body, ..node };
+    }
+
+    Module { body, ..node }.fold_children_with(self)
+  }
+
+  fn fold_ident(&mut self, node: Ident) -> Ident {
+    match self.renames.get(&id!(node)) {
+      Some(name) => Ident {
+        sym: name.clone(),
+        ..node
+      },
+      None => node,
+    }
+  }
+
+  fn fold_export_named_specifier(
+    &mut self,
+    mut node: ExportNamedSpecifier,
+  ) -> ExportNamedSpecifier {
+    // export { foo } -> export { $hash as foo }
+    if let ModuleExportName::Ident(orig) = &node.orig {
+      if node.exported.is_none() && self.renames.contains_key(&id!(orig)) {
+        node.exported = Some(ModuleExportName::Ident(Ident::new(
+          orig.sym.clone(),
+          DUMMY_SP,
+        )));
+      }
+    }
+
+    node.orig = node.orig.fold_with(self);
+    node
+  }
+
+  fn fold_import_named_specifier(
+    &mut self,
+    mut node: ImportNamedSpecifier,
+  ) -> ImportNamedSpecifier {
+    // import { foo } from 'x'; export { foo }; -> import { foo as $hash } from 'x'; ...
+    if node.imported.is_none() && self.renames.contains_key(&id!(node.local)) {
+      node.imported = Some(ModuleExportName::Ident(Ident::new(
+        node.local.sym.clone(),
+        DUMMY_SP,
+      )));
+    }
+
+    node.local = node.local.fold_with(self);
+    node
+  }
+
+  fn fold_prop(&mut self, node: Prop) -> Prop {
+    match node {
+      Prop::Shorthand(ident) if self.renames.contains_key(&id!(ident)) => {
+        Prop::KeyValue(KeyValueProp {
+          key: PropName::Ident(Ident::new(ident.sym.clone(), DUMMY_SP)),
+          value: Box::new(Expr::Ident(ident.fold_with(self))),
+        })
+      }
+      _ => node.fold_children_with(self),
+    }
+  }
+
+  fn fold_prop_name(&mut self, node: PropName) -> PropName {
+    match node {
+      PropName::Computed(k) => PropName::Computed(k.fold_with(self)),
+      k => k,
+    }
+  }
+
+  fn fold_object_pat_prop(&mut self, node: ObjectPatProp) -> ObjectPatProp {
+    // ({ foo } = bar); -> ({ foo: $hash } = bar);
+    match node {
+      ObjectPatProp::Assign(assign) if self.renames.contains_key(&id!(assign.key)) => {
+        ObjectPatProp::KeyValue(KeyValuePatProp {
+          key: PropName::Ident(Ident::new(assign.key.sym.clone(), DUMMY_SP)),
+          value: Box::new(match assign.value {
+            Some(value) => Pat::Assign(AssignPat {
+              left: Box::new(Pat::Ident(assign.key.fold_with(self))),
+              right: value.fold_with(self),
+              span: DUMMY_SP,
+            }),
+            None => Pat::Ident(assign.key.fold_with(self)),
+          }),
+        })
+      }
+      _ => node.fold_children_with(self),
+    }
+  }
+}
@@ -184,8 +184,12 @@ impl<'a> InlineFS<'a> {
           loc: SourceLocation::from(&self.collect.source_map, span),
           specifier: path.to_str().unwrap().into(),
           attributes: None,
+          import_attributes: None,
           is_optional: false,
           is_helper: false,
+          is_awaited: false,
+          is_resolve_only: false,
+          is_context: false,
           source_type: None,
           placeholder: None,
         });
@@ -93,8 +93,12 @@ impl VisitMut for GlobalReplacer<'_> {
             loc: SourceLocation::from(&self.source_map, id.span),
             specifier,
             attributes: None,
+            import_attributes: None,
             is_optional: false,
             is_helper: false,
+            is_awaited: false,
+            is_resolve_only: false,
+            is_context: false,
             source_type: Some(SourceType::Module),
             placeholder: None,
           });
@@ -114,8 +118,12 @@ impl VisitMut for GlobalReplacer<'_> {
             loc: SourceLocation::from(&self.source_map, id.span),
             specifier,
             attributes: None,
+            import_attributes: None,
             is_optional: false,
             is_helper: false,
+            is_awaited: false,
+            is_resolve_only: false,
+            is_context: false,
             source_type: Some(SourceType::Module),
             placeholder: None,
           });
@@ -1,7 +1,5 @@
-use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::hash::Hasher;
 
 use indexmap::IndexMap;
 use serde::Deserialize;
@@ -21,7 +19,10 @@ use crate::collect::Collect;
 use crate::collect::Export;
 use crate::collect::Import;
 use crate::collect::ImportKind;
+use crate::hash;
 use crate::id;
+use crate::utils::export_default_keyword_span;
+use crate::utils::export_keyword_span;
 use crate::utils::get_undefined_ident;
 use crate::utils::is_unresolved;
 use crate::utils::match_export_name;
@@ -35,21 +36,14 @@ use crate::utils::Diagnostic;
 use crate::utils::DiagnosticSeverity;
 use crate::utils::SourceLocation;
 
-macro_rules! hash {
-  ($str:expr) => {{
-    let mut hasher = DefaultHasher::new();
-    hasher.write($str.as_bytes());
-    hasher.finish()
-  }};
-}
-
 pub fn hoist(
   module: Module,
   module_id: &str,
   unresolved_mark: Mark,
   collect: &Collect,
+  reserved_globals: &[String],
 ) -> Result<(Module, HoistResult, Vec<Diagnostic>), Vec<Diagnostic>> {
-  let mut hoist = Hoist::new(module_id, unresolved_mark, collect);
+  let mut hoist = Hoist::new(module_id, unresolved_mark, collect, reserved_globals);
   let module = module.fold_with(&mut hoist);
 
   if !hoist.diagnostics.is_empty() {
@@ -128,6 +122,8 @@ struct Hoist<'a> {
   in_function_scope: bool,
   diagnostics: Vec<Diagnostic>,
   unresolved_mark: Mark,
+  /// Top level names that are left as-is, as the host environment expects them to be globals
+  reserved_globals: &'a [String],
 }
 
 /// Data pertaining to mangled identifiers replacing import and export statements
@@ -336,7 +332,12 @@ pub struct HoistResult {
 }
 
 impl<'a> Hoist<'a> {
-  fn new(module_id: &'a str, unresolved_mark: Mark, collect: &'a Collect) -> Self {
+  fn new(
+    module_id: &'a str,
+    unresolved_mark: Mark,
+    collect: &'a Collect,
+    reserved_globals: &'a [String],
+  ) -> Self {
     Hoist {
       module_id,
       collect,
@@ -351,6 +352,7 @@ impl<'a> Hoist<'a> {
       in_function_scope: false,
       diagnostics: vec![],
       unresolved_mark,
+      reserved_globals,
     }
   }
 
@@ -551,12 +553,16 @@ impl<'a> Fold for Hoist<'a> {
                 source: export.src.value,
                 local: "*".into(),
                 imported: "*".into(),
-                loc: SourceLocation::from(&self.collect.source_map, export.span),
+                loc: SourceLocation::from(
+                  &self.collect.source_map,
+                  export_keyword_span(&self.collect.source_map, export.span),
+                ),
                 kind: ImportKind::Import,
               });
             }
             ModuleDecl::ExportDefaultExpr(export) => {
-              let ident = self.get_export_ident(export.span, &"default".into());
+              let span = export_default_keyword_span(&self.collect.source_map, export.span);
+              let ident = self.get_export_ident(span, &"default".into());
               let init = export.expr.fold_with(self);
               self
                 .module_items
@@ -573,12 +579,13 @@ impl<'a> Fold for Hoist<'a> {
                 })))));
             }
             ModuleDecl::ExportDefaultDecl(export) => {
+              let span = export_default_keyword_span(&self.collect.source_map, export.span);
               let decl = match export.decl {
                 DefaultDecl::Class(class) => Decl::Class(ClassDecl {
                   ident: if self.collect.should_wrap && class.ident.is_some() {
                     class.ident.unwrap()
                   } else {
-                    self.get_export_ident(DUMMY_SP, &"default".into())
+                    self.get_export_ident(span, &"default".into())
                   },
                   declare: false,
                   class: class.class.fold_with(self),
@@ -587,7 +594,7 @@ impl<'a> Fold for Hoist<'a> {
                   ident: if self.collect.should_wrap && func.ident.is_some() {
                     func.ident.unwrap()
                   } else {
-                    self.get_export_ident(DUMMY_SP, &"default".into())
+                    self.get_export_ident(span, &"default".into())
                   },
                   declare: false,
                   function: func.function.fold_with(self),
@@ -1105,6 +1112,7 @@ impl<'a> Fold for Hoist<'a> {
     if node.span.has_mark(self.collect.global_mark)
       && !is_unresolved(&node, self.unresolved_mark)
       && !self.collect.should_wrap
+      && !self.reserved_globals.iter().any(|name| *name == *node.sym)
     {
       let new_name: JsWord = format!("${}$var${}", self.module_id, node.sym).into();
       return Ident::new(new_name, node.span);
@@ -1420,7 +1428,7 @@ mod tests {
             module.visit_with(&mut collect);
 
             let (module, res) = {
-              let mut hoist = Hoist::new("abc", unresolved_mark, &collect);
+              let mut hoist = Hoist::new("abc", unresolved_mark, &collect, &[]);
               let module = module.fold_with(&mut hoist);
               (module, hoist.get_result())
             };
@@ -1687,6 +1695,26 @@ mod tests {
     assert_eq_set!(collect.used_imports, set! { w!("bar") });
   }
 
+  #[test]
+  fn collect_unused_imports() {
+    let (collect, _code, _hoist) = parse(
+      r#"
+    import {used, unused} from 'source';
+    import {reexported} from 'other';
+    import * as ns from 'namespace';
+
+    used();
+    export {reexported};
+    "#,
+    );
+
+    let unused_imports = collect.unused_imports();
+
+    assert_eq!(unused_imports.len(), 1);
+    assert_eq!(unused_imports[0].local, w!("unused"));
+    assert_eq!(unused_imports[0].source, w!("source"));
+  }
+
   #[test]
   fn collect_cjs_reassign() {
     let (collect, _code, _hoist) = parse(
@@ -3679,9 +3707,9 @@ mod tests {
           specifier: "default".into(),
           loc: SourceLocation {
             start_line: 1,
-            start_col: 1,
+            start_col: 8,
             end_line: 1,
-            end_col: 30
+            end_col: 15
           },
           is_esm: true
         }
@@ -3697,9 +3725,9 @@ mod tests {
           specifier: "test".into(),
           loc: SourceLocation {
             start_line: 1,
-            start_col: 1,
+            start_col: 8,
             end_line: 1,
-            end_col: 35
+            end_col: 15
           },
           is_esm: true
         }
@@ -3715,9 +3743,9 @@ mod tests {
           specifier: "default".into(),
           loc: SourceLocation {
             start_line: 1,
-            start_col: 1,
+            start_col: 8,
             end_line: 1,
-            end_col: 24
+            end_col: 15
           },
           is_esm: true
         }
@@ -3733,9 +3761,9 @@ mod tests {
           specifier: "test".into(),
           loc: SourceLocation {
             start_line: 1,
-            start_col: 1,
+            start_col: 8,
             end_line: 1,
-            end_col: 29
+            end_col: 15
           },
           is_esm: true
         }
@@ -3751,9 +3779,9 @@ mod tests {
           specifier: "default".into(),
           loc: SourceLocation {
             start_line: 1,
-            start_col: 1,
+            start_col: 8,
             end_line: 1,
-            end_col: 20
+            end_col: 15
           },
           is_esm: true
         }
@@ -0,0 +1,74 @@
+use swc_core::common::sync::Lrc;
+use swc_core::common::Mark;
+use swc_core::common::SourceMap;
+use swc_core::ecma::ast::CallExpr;
+use swc_core::ecma::ast::Callee;
+use swc_core::ecma::ast::Expr;
+use swc_core::ecma::ast::ExprOrSpread;
+use swc_core::ecma::ast::Lit;
+use swc_core::ecma::ast::Str;
+use swc_core::ecma::visit::Visit;
+use swc_core::ecma::visit::VisitWith;
+
+use crate::utils::is_unresolved;
+use crate::utils::match_member_expr;
+use crate::utils::SourceLocation;
+
+/// Collects the string literal arguments of calls to translation functions
+///
+/// For example, when `t` is a translation function, this collects `hello` from `t('hello')`.
+/// Function names may contain dots to match member expressions, such as `i18n.t`. Calls to
+/// functions that are shadowed by a local variable are ignored.
+pub struct I18nCollector<'a> {
+  functions: &'a [String],
+  keys: &'a mut Vec<(String, SourceLocation)>,
+  source_map: Lrc<SourceMap>,
+  unresolved_mark: Mark,
+}
+
+impl<'a> I18nCollector<'a> {
+  pub fn new(
+    functions: &'a [String],
+    keys: &'a mut Vec<(String, SourceLocation)>,
+    source_map: Lrc<SourceMap>,
+    unresolved_mark: Mark,
+  ) -> Self {
+    Self {
+      functions,
+      keys,
+      source_map,
+      unresolved_mark,
+    }
+  }
+
+  fn is_translation_function(&self, callee: &Expr) -> bool {
+    self.functions.iter().any(|function| match callee {
+      Expr::Ident(ident) => {
+        &*ident.sym == function.as_str() && is_unresolved(ident, self.unresolved_mark)
+      }
+      Expr::Member(member) if function.contains('.') => {
+        match_member_expr(member, function.split('.').collect(), self.unresolved_mark)
+      }
+      _ => false,
+    })
+  }
+}
+
+impl Visit for I18nCollector<'_> {
+  fn visit_call_expr(&mut self, node: &CallExpr) {
+    if let Callee::Expr(callee) = &node.callee {
+      if self.is_translation_function(callee) {
+        if let Some(ExprOrSpread { spread: None, expr }) = node.args.first() {
+          if let Expr::Lit(Lit::Str(Str { value, span, .. })) = &**expr {
+            self.keys.push((
+              value.to_string(),
+              SourceLocation::from(&self.source_map, *span),
+            ));
+          }
+        }
+      }
+    }
+
+    node.visit_children_with(self);
+  }
+}
@@ -1,16 +1,24 @@
+mod block_preserver;
+mod cjs_globals_resolver;
 mod collect;
 mod constant_module;
+mod css_in_js_collector;
 mod dependency_collector;
 mod env_replacer;
+mod environment_check_replacer;
+mod export_obfuscator;
 mod fs;
 mod global_replacer;
 mod hoist;
+mod i18n_collector;
 mod modules;
 mod node_replacer;
+mod pure_annotator;
 #[cfg(test)]
 mod test_utils;
 mod typeof_replacer;
 mod utils;
+mod var_checker;
 
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -18,20 +26,27 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
 
+use block_preserver::BlockPreserver;
+use cjs_globals_resolver::CjsGlobalsResolver;
 use collect::Collect;
+pub use collect::CollectDuplicateExport;
 pub use collect::CollectImportedSymbol;
 use collect::CollectResult;
 use constant_module::ConstantModule;
+use css_in_js_collector::CssInJsCollector;
 pub use dependency_collector::dependency_collector;
 pub use dependency_collector::DependencyDescriptor;
 pub use dependency_collector::DependencyKind;
 use env_replacer::*;
+use environment_check_replacer::EnvironmentCheckReplacer;
+use export_obfuscator::ExportObfuscator;
 use fs::inline_fs;
 use global_replacer::GlobalReplacer;
 use hoist::hoist;
 pub use hoist::ExportedSymbol;
 use hoist::HoistResult;
 pub use hoist::ImportedSymbol;
+use i18n_collector::I18nCollector;
 use indexmap::IndexMap;
 use modules::esm2cjs;
 use node_replacer::NodeReplacer;
@@ -39,6 +54,7 @@ use parcel_macros::MacroCallback;
 use parcel_macros::MacroError;
 use parcel_macros::Macros;
 use path_slash::PathExt;
+use pure_annotator::PureAnnotator;
 use serde::Deserialize;
 use serde::Serialize;
 use swc_core::common::chain;
@@ -51,10 +67,16 @@ use swc_core::common::FileName;
 use swc_core::common::Globals;
 use swc_core::common::Mark;
 use swc_core::common::SourceMap;
+use swc_core::ecma::ast::Decl;
 use swc_core::ecma::ast::Module;
 use swc_core::ecma::ast::ModuleItem;
 use swc_core::ecma::ast::Program;
+use swc_core::ecma::ast::Stmt;
 use swc_core::ecma::codegen::text_writer::JsWriter;
+use swc_core::ecma::minifier::optimize;
+use swc_core::ecma::minifier::option::ExtraOptions;
+use swc_core::ecma::minifier::option::MangleOptions;
+use swc_core::ecma::minifier::option::MinifyOptions;
 use swc_core::ecma::parser::error::Error;
 use swc_core::ecma::parser::lexer::Lexer;
 use swc_core::ecma::parser::EsSyntax;
@@ -63,6 +85,7 @@ use swc_core::ecma::parser::StringInput;
 use swc_core::ecma::parser::Syntax;
 use swc_core::ecma::parser::TsSyntax;
 use swc_core::ecma::preset_env::preset_env;
+use swc_core::ecma::preset_env::Feature;
 use swc_core::ecma::preset_env::Mode::Entry;
 use swc_core::ecma::preset_env::Targets;
 use swc_core::ecma::preset_env::Version;
@@ -73,26 +96,35 @@ use swc_core::ecma::transforms::base::helpers;
 use swc_core::ecma::transforms::base::hygiene::hygiene;
 use swc_core::ecma::transforms::base::resolver;
 use swc_core::ecma::transforms::base::Assumptions;
+use swc_core::ecma::transforms::compat;
 use swc_core::ecma::transforms::compat::reserved_words::reserved_words;
 use swc_core::ecma::transforms::optimization::simplify::dead_branch_remover;
 use swc_core::ecma::transforms::optimization::simplify::expr_simplifier;
 use swc_core::ecma::transforms::proposal::decorators;
 use swc_core::ecma::transforms::react;
 use swc_core::ecma::transforms::typescript;
+use swc_core::ecma::visit::Fold;
+use swc_core::ecma::visit::VisitMutWith;
 use swc_core::ecma::visit::VisitWith;
 use swc_core::ecma::visit::{as_folder, FoldWith};
 use typeof_replacer::*;
 use utils::error_buffer_to_diagnostics;
-use utils::CodeHighlight;
-use utils::Diagnostic;
-use utils::DiagnosticSeverity;
+pub use utils::CodeHighlight;
+pub use utils::Diagnostic;
+pub use utils::DiagnosticSeverity;
 use utils::ErrorBuffer;
+pub use utils::EsVersion;
+pub use utils::InteropStrategy;
 pub use utils::SourceLocation;
 pub use utils::SourceType;
+use var_checker::VarChecker;
 
 type SourceMapBuffer = Vec<(swc_core::common::BytePos, swc_core::common::LineCol)>;
 
-#[derive(Default, Serialize, Debug, Deserialize)]
+/// The version of this crate, which changes whenever its output or the SWC version does
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Clone, Default, Serialize, Debug, Deserialize)]
 pub struct Config {
   pub filename: String,
   #[serde(with = "serde_bytes")]
@@ -118,6 +150,8 @@ pub struct Config {
   pub react_refresh: bool,
   pub targets: Option<HashMap<String, String>>,
   pub source_maps: bool,
+  /// Embed the original source code in the `sourcesContent` of the source map
+  pub source_map_include_content: bool,
   pub scope_hoist: bool,
   pub source_type: SourceType,
   pub supports_module_workers: bool,
@@ -127,6 +161,39 @@ pub struct Config {
   pub is_swc_helpers: bool,
   pub standalone: bool,
   pub inline_constants: bool,
+  pub minify: bool,
+  pub keep_names: bool,
+  pub i18n_functions: Vec<String>,
+  pub strict_dynamic_imports: bool,
+  /// The number of spaces to indent the output with, or 0 to use the default indentation
+  ///
+  /// When this is set, blocks with a single statement are kept rather than unwrapped. This is
+  /// ignored when minifying.
+  pub output_indent: usize,
+  /// Leave JSX untransformed in the output, for tooling that compiles it downstream
+  pub preserve_jsx: bool,
+  /// Annotate top-level calls that create exported values with `/*#__PURE__*/`
+  pub annotate_pure: bool,
+  /// Collect URL dependencies for `fetch` calls with a relative string literal URL
+  pub follow_fetch_urls: bool,
+  /// Lower the output to this ECMAScript version, which takes precedence over `targets`
+  pub target_es_version: Option<EsVersion>,
+  /// Report a warning for each `var` declaration in ES modules
+  pub ban_var: bool,
+  /// Global names that are neither renamed by scope hoisting nor mangled by the minifier
+  pub reserved_globals: Vec<String>,
+  /// Import SWC helpers from `@swc/helpers` even in scripts, rather than inlining them
+  pub external_helpers: bool,
+  /// How default imports of CommonJS modules are resolved, when the output is not scope hoisted
+  pub interop: InteropStrategy,
+  /// Collect the CSS of CSS-in-JS tagged templates, such as `styled.div\`...\``
+  pub extract_css_in_js: bool,
+  /// Rename the local variables of exports that are not in this list, to hide internal names
+  pub obfuscate_exports: Option<Vec<String>>,
+  /// Replace `typeof window` based on `is_browser` and `is_worker`, so feature checks are folded
+  pub fold_environment_checks: bool,
+  /// Collect dynamic imports of relative template literals as glob dependencies
+  pub context_dynamic_imports: bool,
 }
 
 #[derive(Serialize, Debug, Default)]
@@ -139,11 +206,23 @@ pub struct TransformResult {
   pub dependencies: Vec<DependencyDescriptor>,
   pub hoist_result: Option<HoistResult>,
   pub symbol_result: Option<CollectResult>,
+  /// ES module imports that are never referenced within the module
+  pub unused_imports: Vec<CollectImportedSymbol>,
+  /// ES module exports that reuse the name of another export
+  pub duplicate_exports: Vec<CollectDuplicateExport>,
   pub diagnostics: Option<Vec<Diagnostic>>,
   pub needs_esm_helpers: bool,
   pub used_env: HashSet<swc_core::ecma::atoms::JsWord>,
   pub has_node_replacements: bool,
   pub is_constant_module: bool,
+  pub i18n_keys: Vec<(String, SourceLocation)>,
+  /// The CSS of CSS-in-JS tagged templates along with their locations, when `extract_css_in_js` is set
+  pub extracted_css: Vec<(String, SourceLocation)>,
+  /// The names of the SWC helpers that were inlined into the module, such as `_async_to_generator`
+  ///
+  /// Helpers are only inlined into scripts, modules import them from `@swc/helpers` instead, as do
+  /// scripts when `external_helpers` is set.
+  pub injected_helpers: Vec<swc_core::ecma::atoms::JsWord>,
 }
 
 fn targets_to_versions(targets: &Option<HashMap<String, String>>) -> Option<Versions> {
@@ -176,6 +255,54 @@ fn targets_to_versions(targets: &Option<HashMap<String, String>>) -> Option<Vers
   None
 }
 
+/// The SWC compat passes that lower the syntax newer than `target`
+fn compat_passes<'a>(
+  target: Option<swc_core::ecma::ast::EsVersion>,
+  unresolved_mark: Mark,
+  comments: &'a SingleThreadedComments,
+) -> impl Fold + 'a {
+  use swc_core::ecma::ast::EsVersion::*;
+
+  let target = target.unwrap_or(EsNext);
+  chain!(
+    Optional::new(
+      compat::es2022::es2022(Some(comments), Default::default(), unresolved_mark),
+      target < Es2022
+    ),
+    Optional::new(compat::es2021::es2021(), target < Es2021),
+    Optional::new(
+      compat::es2020::es2020(Default::default(), unresolved_mark),
+      target < Es2020
+    ),
+    Optional::new(compat::es2019::es2019(), target < Es2019),
+    Optional::new(compat::es2018::es2018(Default::default()), target < Es2018),
+    Optional::new(
+      compat::es2017::es2017(Default::default(), Some(comments), unresolved_mark),
+      target < Es2017
+    ),
+    Optional::new(compat::es2016::es2016(), target < Es2016),
+    Optional::new(
+      compat::es2015::es2015(unresolved_mark, Some(comments), Default::default()),
+      target < Es2015
+    ),
+  )
+}
+
+fn top_level_decl_names(body: &[ModuleItem]) -> Vec<swc_core::ecma::atoms::JsWord> {
+  body
+    .iter()
+    .filter_map(|item| match item {
+      ModuleItem::Stmt(Stmt::Decl(Decl::Fn(func))) => Some(func.ident.sym.clone()),
+      ModuleItem::Stmt(Stmt::Decl(Decl::Var(var))) => var
+        .decls
+        .first()
+        .and_then(|decl| decl.name.as_ident())
+        .map(|ident| ident.sym.clone()),
+      _ => None,
+    })
+    .collect()
+}
+
 pub fn transform(
   config: Config,
   call_macro: Option<MacroCallback>,
@@ -211,15 +338,20 @@ pub fn transform(
         Program::Script(script) => script.shebang.take().map(|s| s.to_string()),
       };
 
+      if config.annotate_pure {
+        module.visit_with(&mut PureAnnotator::new(&comments));
+      }
+
       let mut global_deps = vec![];
       let mut fs_deps = vec![];
       let should_inline_fs = config.inline_fs
         && config.source_type != SourceType::Script
         && code.contains("readFileSync");
-      let should_import_swc_helpers = match config.source_type {
-        SourceType::Module => true,
-        SourceType::Script => false,
-      };
+      let should_import_swc_helpers = config.external_helpers
+        || match config.source_type {
+          SourceType::Module => true,
+          SourceType::Script => false,
+        };
 
       swc_core::common::GLOBALS.set(&Globals::new(), || {
         let error_buffer = ErrorBuffer::default();
@@ -292,7 +424,7 @@ pub fn transform(
               let is_module = module.is_module();
               // If it's a script, convert into module. This needs to happen after
               // the resolver (which behaves differently for non-/strict mode).
-              let module = match module {
+              let mut module = match module {
                 Program::Module(module) => module,
                 Program::Script(script) => Module {
                   span: script.span,
@@ -301,6 +433,14 @@ pub fn transform(
                 },
               };
 
+              if config.is_type_script {
+                module.visit_mut_with(&mut CjsGlobalsResolver::new(
+                  &module,
+                  global_mark,
+                  unresolved_mark,
+                ));
+              }
+
               let mut module = module.fold_with(&mut Optional::new(
                 react::react(
                   source_map.clone(),
@@ -309,20 +449,33 @@ pub fn transform(
                   global_mark,
                   unresolved_mark,
                 ),
-                config.is_jsx,
+                config.is_jsx && !config.preserve_jsx,
               ));
 
               let mut preset_env_config = swc_core::ecma::preset_env::Config {
                 dynamic_import: true,
                 ..Default::default()
               };
-              let versions = targets_to_versions(&config.targets);
+              // An explicit ECMAScript target replaces the browser targets, and is lowered with
+              // the compat passes for each newer version rather than with preset_env.
+              let es_target = config.target_es_version.map(|es_version| es_version.to_swc());
+              let versions = match es_target {
+                Some(_) => None,
+                None => targets_to_versions(&config.targets),
+              };
+              let supports_arrow_functions = match es_target {
+                Some(target) => target >= swc_core::ecma::ast::EsVersion::Es2015,
+                None => !matches!(versions, Some(versions) if Feature::ArrowFunctions.should_enable(versions, true, false)),
+              };
               let mut should_run_preset_env = false;
+              let mut should_run_compat = false;
               if !config.is_swc_helpers {
                 // Avoid transpiling @swc/helpers so that we don't cause infinite recursion.
                 // Filter the versions for preset_env only so that syntax support checks
                 // (e.g. in esm2cjs) still work correctly.
-                if let Some(versions) = versions {
+                if let Some(target) = es_target {
+                  should_run_compat = target < swc_core::ecma::ast::EsVersion::EsNext;
+                } else if let Some(versions) = versions {
                   should_run_preset_env = true;
                   preset_env_config.targets = Some(Targets::Versions(versions));
                   preset_env_config.shipped_proposals = true;
@@ -345,6 +498,26 @@ pub fn transform(
                 }
               }
 
+              if !config.i18n_functions.is_empty() {
+                module.visit_with(&mut I18nCollector::new(
+                  &config.i18n_functions,
+                  &mut result.i18n_keys,
+                  source_map.clone(),
+                  unresolved_mark,
+                ));
+              }
+
+              if config.extract_css_in_js {
+                module.visit_with(&mut CssInJsCollector::new(
+                  &mut result.extracted_css,
+                  source_map.clone(),
+                ));
+              }
+
+              if config.ban_var && config.source_type != SourceType::Script {
+                module.visit_with(&mut VarChecker::new(&mut diagnostics, source_map.clone()));
+              }
+
               if config.scope_hoist && config.inline_constants {
                 let mut constant_module = ConstantModule::new();
                 module.visit_with(&mut constant_module);
@@ -357,6 +530,14 @@ pub fn transform(
                     as_folder(TypeofReplacer::new(unresolved_mark)),
                     config.source_type != SourceType::Script,
                   ),
+                  Optional::new(
+                    as_folder(EnvironmentCheckReplacer::new(
+                      unresolved_mark,
+                      config.is_browser,
+                      config.is_worker
+                    )),
+                    config.fold_environment_checks,
+                  ),
                   // Inline process.env and process.browser,
                   Optional::new(
                     as_folder(EnvReplacer {
@@ -390,7 +571,19 @@ pub fn transform(
                   ),
                 );
 
-                module.fold_with(&mut passes)
+                // Record the blocks before they are unwrapped by the dead branch remover, so that
+                // the output keeps the structure of the source when it is indented
+                let mut block_preserver = BlockPreserver::default();
+                if config.output_indent > 0 {
+                  module.visit_with(&mut block_preserver);
+                }
+
+                let mut module = module.fold_with(&mut passes);
+                if config.output_indent > 0 {
+                  module.visit_mut_with(&mut block_preserver);
+                }
+
+                module
               };
 
               let module = module.fold_with(
@@ -436,19 +629,33 @@ pub fn transform(
                     ),
                     should_run_preset_env,
                   ),
-                  // Inject SWC helpers if needed.
-                  helpers::inject_helpers(global_mark),
+                  // Transpile syntax newer than the ECMAScript target version
+                  Optional::new(
+                    compat_passes(es_target, unresolved_mark, &comments),
+                    should_run_compat,
+                  ),
                 );
 
-                module.fold_with(&mut passes)
+                let module = module.fold_with(&mut passes);
+
+                // Inject SWC helpers if needed. Inlined helpers are declared at the top level, so
+                // they can be told apart by comparing the top level declarations.
+                let declared: HashSet<_> = top_level_decl_names(&module.body).into_iter().collect();
+                let module = module.fold_with(&mut helpers::inject_helpers(global_mark));
+                result.injected_helpers = top_level_decl_names(&module.body)
+                  .into_iter()
+                  .filter(|name| !declared.contains(name))
+                  .collect();
+
+                module
               };
 
               // Flush Id=(JsWord, SyntaxContexts) into unique names and reresolve to
               // set global_mark for all nodes, even generated ones.
               // - This will also remove any other other marks (like ignore_mark)
-              // This only needs to be done if preset_env ran because all other transforms
-              // insert declarations with global_mark (even though they are generated).
-              let module = if config.scope_hoist && should_run_preset_env {
+              // This only needs to be done if preset_env or the compat passes ran because all other
+              // transforms insert declarations with global_mark (even though they are generated).
+              let module = if config.scope_hoist && (should_run_preset_env || should_run_compat) {
                 module.fold_with(&mut chain!(
                   hygiene(),
                   resolver(unresolved_mark, global_mark, false)
@@ -480,6 +687,11 @@ pub fn transform(
                 return Ok(result);
               }
 
+              let module = match &config.obfuscate_exports {
+                Some(keep) => module.fold_with(&mut ExportObfuscator::new(keep, &config.module_id)),
+                None => module,
+              };
+
               let mut collect = Collect::new(
                 source_map.clone(),
                 unresolved_mark,
@@ -492,9 +704,17 @@ pub fn transform(
               if let Some(bailouts) = &collect.bailouts {
                 diagnostics.extend(bailouts.iter().map(|bailout| bailout.to_diagnostic()));
               }
+              result.unused_imports = collect.unused_imports();
+              result.duplicate_exports = collect.duplicate_exports.clone();
 
               let module = if config.scope_hoist {
-                let res = hoist(module, config.module_id.as_str(), unresolved_mark, &collect);
+                let res = hoist(
+                  module,
+                  config.module_id.as_str(),
+                  unresolved_mark,
+                  &collect,
+                  &config.reserved_globals,
+                );
                 match res {
                   Ok((module, hoist_result, hoist_diagnostics)) => {
                     result.hoist_result = Some(hoist_result);
@@ -512,11 +732,48 @@ pub fn transform(
                   result.symbol_result = Some(collect.into());
                 }
 
-                let (module, needs_helpers) = esm2cjs(module, unresolved_mark, versions);
+                let (module, needs_helpers) =
+                  esm2cjs(
+                  module,
+                  unresolved_mark,
+                  supports_arrow_functions,
+                  config.interop,
+                );
                 result.needs_esm_helpers = needs_helpers;
                 module
               };
 
+              let module = if config.minify {
+                optimize(
+                  Program::Module(module),
+                  source_map.clone(),
+                  Some(&comments),
+                  None,
+                  &MinifyOptions {
+                    mangle: Some(MangleOptions {
+                      keep_class_names: config.keep_names,
+                      keep_fn_names: config.keep_names,
+                      // Top level names are referenced by symbols and other assets
+                      top_level: Some(false),
+                      reserved: config
+                        .reserved_globals
+                        .iter()
+                        .map(|name| name.as_str().into())
+                        .collect(),
+                      ..Default::default()
+                    }),
+                    ..Default::default()
+                  },
+                  &ExtraOptions {
+                    unresolved_mark,
+                    top_level_mark: global_mark,
+                  },
+                )
+                .expect_module()
+              } else {
+                module
+              };
+
               let module = module.fold_with(&mut chain!(
                 reserved_words(),
                 hygiene(),
@@ -530,11 +787,26 @@ pub fn transform(
                 result.diagnostics = Some(diagnostics);
               }
 
-              let (buf, src_map_buf) =
-                emit(source_map.clone(), comments, &module, config.source_maps)?;
+              let (buf, src_map_buf) = emit(
+                source_map.clone(),
+                comments,
+                &module,
+                config.source_maps,
+                if config.minify {
+                  0
+                } else {
+                  config.output_indent
+                },
+              )?;
               if config.source_maps
                 && source_map
-                  .build_source_map_with_config(&src_map_buf, None, SourceMapConfig)
+                  .build_source_map_with_config(
+                    &src_map_buf,
+                    None,
+                    SourceMapConfig {
+                      include_content: config.source_map_include_content,
+                    },
+                  )
                   .to_writer(&mut map_buf)
                   .is_ok()
               {
@@ -612,16 +884,20 @@ fn parse(
   Ok((module, comments))
 }
 
+/// The widest indentation that can be configured for the output
+const MAX_INDENT: &str = "                ";
+
 fn emit(
   source_map: Lrc<SourceMap>,
   comments: SingleThreadedComments,
   module: &Module,
   source_maps: bool,
+  indent: usize,
 ) -> Result<(Vec<u8>, SourceMapBuffer), std::io::Error> {
   let mut src_map_buf = vec![];
   let mut buf = vec![];
   {
-    let writer = Box::new(JsWriter::new(
+    let mut writer = JsWriter::new(
       source_map.clone(),
       "\n",
       &mut buf,
@@ -630,7 +906,12 @@ fn emit(
       } else {
         None
       },
-    ));
+    );
+    if indent > 0 {
+      writer.set_indent_str(&MAX_INDENT[..indent.min(MAX_INDENT.len())]);
+    }
+
+    let writer = Box::new(writer);
     let config = swc_core::ecma::codegen::Config::default()
       .with_target(swc_core::ecma::ast::EsVersion::Es5)
       // Make sure the output works regardless of whether it's loaded with the correct (utf8) encoding
@@ -649,12 +930,19 @@ fn emit(
 }
 
 // Exclude macro expansions from source maps.
-struct SourceMapConfig;
+struct SourceMapConfig {
+  include_content: bool,
+}
+
 impl SourceMapGenConfig for SourceMapConfig {
   fn file_name_to_source(&self, f: &FileName) -> String {
     f.to_string()
   }
 
+  fn inline_sources_content(&self, _f: &FileName) -> bool {
+    self.include_content
+  }
+
   fn skip(&self, f: &FileName) -> bool {
     matches!(f, FileName::MacroExpansion | FileName::Internal(..))
   }
@@ -9,8 +9,6 @@ use swc_core::common::DUMMY_SP;
 use swc_core::ecma::ast::*;
 use swc_core::ecma::atoms::js_word;
 use swc_core::ecma::atoms::JsWord;
-use swc_core::ecma::preset_env::Feature;
-use swc_core::ecma::preset_env::Versions;
 use swc_core::ecma::utils::stack_size::maybe_grow_default;
 use swc_core::ecma::visit::Fold;
 use swc_core::ecma::visit::FoldWith;
@@ -20,8 +18,14 @@ use crate::id;
 use crate::utils::get_undefined_ident;
 use crate::utils::match_export_name;
 use crate::utils::match_export_name_ident;
+use crate::utils::InteropStrategy;
 
-pub fn esm2cjs(node: Module, unresolved_mark: Mark, versions: Option<Versions>) -> (Module, bool) {
+pub fn esm2cjs(
+  node: Module,
+  unresolved_mark: Mark,
+  supports_arrow_functions: bool,
+  interop: InteropStrategy,
+) -> (Module, bool) {
   let mut fold = ESMFold {
     imports: HashMap::new(),
     require_names: HashMap::new(),
@@ -33,7 +37,8 @@ pub fn esm2cjs(node: Module, unresolved_mark: Mark, versions: Option<Versions>)
     in_function_scope: false,
     mark: Mark::fresh(Mark::root()),
     unresolved_mark,
-    versions,
+    supports_arrow_functions,
+    interop,
   };
 
   let module = node.fold_with(&mut fold);
@@ -56,7 +61,8 @@ struct ESMFold {
   in_function_scope: bool,
   mark: Mark,
   unresolved_mark: Mark,
-  versions: Option<Versions>,
+  supports_arrow_functions: bool,
+  interop: InteropStrategy,
 }
 
 fn local_name_for_src(src: &JsWord) -> JsWord {
@@ -119,7 +125,8 @@ impl ESMFold {
   }
 
   fn create_interop_default(&mut self, src: JsWord) {
-    if self.interops.contains(&src) {
+    // Only the Babel strategy needs a helper to check the `__esModule` flag at runtime
+    if self.interop != InteropStrategy::Babel || self.interops.contains(&src) {
       return;
     }
 
@@ -179,7 +186,7 @@ impl ESMFold {
       vec![
         Expr::Ident(Ident::new("exports".into(), DUMMY_SP)),
         Expr::Lit(Lit::Str(exported.into())),
-        if matches!(self.versions, Some(versions) if Feature::ArrowFunctions.should_enable(versions, true, false)) {
+        if !self.supports_arrow_functions {
           Expr::Fn(FnExpr {
             ident: None,
             function: Box::new(Function {
@@ -235,12 +242,12 @@ impl ESMFold {
   }
 
   fn create_import_access(&mut self, source: &JsWord, imported: &JsWord, span: Span) -> Expr {
-    if imported == "*" {
+    if imported == "*" || (imported == "default" && self.interop == InteropStrategy::Node) {
       let name = self.get_require_name(source, span);
       return Expr::Ident(name);
     }
 
-    let obj = if imported == "default" {
+    let obj = if imported == "default" && self.interop == InteropStrategy::Babel {
       self.get_interop_default_name(source)
     } else {
       self.get_require_name(source, DUMMY_SP)
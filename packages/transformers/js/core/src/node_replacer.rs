@@ -108,8 +108,12 @@ impl<'a> VisitMut for NodeReplacer<'a> {
                 loc: SourceLocation::from(&self.source_map, id.span),
                 specifier: path_module_specifier,
                 attributes: None,
+                import_attributes: None,
                 is_optional: false,
                 is_helper: false,
+                is_awaited: false,
+                is_resolve_only: false,
+                is_context: false,
                 source_type: Some(SourceType::Module),
                 placeholder: None,
               });
@@ -159,8 +163,12 @@ impl<'a> VisitMut for NodeReplacer<'a> {
                 loc: SourceLocation::from(&self.source_map, id.span),
                 specifier: path_module_specifier,
                 attributes: None,
+                import_attributes: None,
                 is_optional: false,
                 is_helper: false,
+                is_awaited: false,
+                is_resolve_only: false,
+                is_context: false,
                 source_type: Some(SourceType::Module),
                 placeholder: None,
               });
@@ -0,0 +1,69 @@
+use swc_core::common::comments::Comments;
+use swc_core::common::Span;
+use swc_core::ecma::ast::{Decl, Expr, ModuleDecl, ModuleItem, Program};
+use swc_core::ecma::visit::Visit;
+
+/// Annotates top-level calls that create exported values with `/*#__PURE__*/`
+///
+/// This lets minifiers drop the calls when the export ends up unused, for example:
+///
+/// ```skip
+/// export const x = makeThing();
+/// export default createStore();
+/// ```
+///
+/// Calls that are already annotated are left untouched.
+pub struct PureAnnotator<'a, C: Comments> {
+  comments: &'a C,
+}
+
+impl<'a, C: Comments> PureAnnotator<'a, C> {
+  pub fn new(comments: &'a C) -> Self {
+    Self { comments }
+  }
+
+  fn annotate(&self, expr: &Expr) {
+    let span = match expr {
+      Expr::Call(call) => call.span,
+      Expr::New(new) => new.span,
+      Expr::Paren(paren) => return self.annotate(&paren.expr),
+      _ => return,
+    };
+
+    self.add_pure_comment(span);
+  }
+
+  fn add_pure_comment(&self, span: Span) {
+    if span.is_dummy() || self.comments.has_flag(span.lo, "PURE") {
+      return;
+    }
+
+    self.comments.add_pure_comment(span.lo);
+  }
+}
+
+impl<'a, C: Comments> Visit for PureAnnotator<'a, C> {
+  fn visit_program(&mut self, program: &Program) {
+    let Program::Module(module) = program else {
+      return;
+    };
+
+    for item in &module.body {
+      match item {
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => {
+          if let Decl::Var(var) = &export.decl {
+            for declarator in &var.decls {
+              if let Some(init) = &declarator.init {
+                self.annotate(init);
+              }
+            }
+          }
+        }
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export)) => {
+          self.annotate(&export.expr);
+        }
+        _ => {}
+      }
+    }
+  }
+}
@@ -4,8 +4,10 @@ use serde::Deserialize;
 use serde::Serialize;
 use swc_core::common::errors::DiagnosticBuilder;
 use swc_core::common::errors::Emitter;
+use swc_core::common::BytePos;
 use swc_core::common::Mark;
 use swc_core::common::SourceMap;
+use swc_core::common::SourceMapper;
 use swc_core::common::Span;
 use swc_core::common::SyntaxContext;
 use swc_core::common::DUMMY_SP;
@@ -133,6 +135,44 @@ pub fn match_export_name_ident(name: &ast::ModuleExportName) -> &ast::Ident {
   }
 }
 
+/// Returns the span of the `default` keyword of an `export default` declaration or expression
+///
+/// The AST does not keep track of keyword locations, so these are recovered from the source code.
+/// The span of the whole statement is returned if the keyword can't be found.
+pub fn export_default_keyword_span(source_map: &SourceMap, span: Span) -> Span {
+  let offset = source_map.span_to_snippet(span).ok().and_then(|snippet| {
+    snippet
+      .strip_prefix("export")
+      .and_then(|rest| rest.find("default"))
+      .map(|index| "export".len() + index)
+  });
+
+  match offset {
+    Some(offset) => {
+      let lo = span.lo + BytePos(offset as u32);
+      Span {
+        lo,
+        hi: lo + BytePos("default".len() as u32),
+        ..span
+      }
+    }
+    None => span,
+  }
+}
+
+/// Returns the span of the `export` keyword of an export statement, such as `export * from 'x'`
+///
+/// The span of the whole statement is returned if it does not start with the keyword.
+pub fn export_keyword_span(source_map: &SourceMap, span: Span) -> Span {
+  match source_map.span_to_snippet(span) {
+    Ok(snippet) if snippet.starts_with("export") => Span {
+      hi: span.lo + BytePos("export".len() as u32),
+      ..span
+    },
+    _ => span,
+  }
+}
+
 pub fn match_require(node: &ast::Expr, unresolved_mark: Mark, ignore_mark: Mark) -> Option<JsWord> {
   use ast::*;
 
@@ -295,6 +335,54 @@ impl Default for SourceType {
   }
 }
 
+/// How default imports of CommonJS modules are resolved when converting ES modules to CommonJS
+#[derive(Serialize, Debug, Deserialize, Eq, PartialEq, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InteropStrategy {
+  /// The default import is `exports.default` if the module has an `__esModule` flag, or
+  /// `module.exports` otherwise
+  #[default]
+  Babel,
+  /// The default import is always `module.exports`, as in Node
+  Node,
+  /// The default import is always `exports.default`
+  None,
+}
+
+/// An ECMAScript version that the output is lowered to
+#[derive(Serialize, Debug, Deserialize, Eq, PartialEq, PartialOrd, Ord, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum EsVersion {
+  Es5,
+  Es2015,
+  Es2016,
+  Es2017,
+  Es2018,
+  Es2019,
+  Es2020,
+  Es2021,
+  Es2022,
+  EsNext,
+}
+
+impl EsVersion {
+  /// The SWC target for this ECMAScript version, used to pick the compat passes to run
+  pub fn to_swc(&self) -> ast::EsVersion {
+    match self {
+      EsVersion::Es5 => ast::EsVersion::Es5,
+      EsVersion::Es2015 => ast::EsVersion::Es2015,
+      EsVersion::Es2016 => ast::EsVersion::Es2016,
+      EsVersion::Es2017 => ast::EsVersion::Es2017,
+      EsVersion::Es2018 => ast::EsVersion::Es2018,
+      EsVersion::Es2019 => ast::EsVersion::Es2019,
+      EsVersion::Es2020 => ast::EsVersion::Es2020,
+      EsVersion::Es2021 => ast::EsVersion::Es2021,
+      EsVersion::Es2022 => ast::EsVersion::Es2022,
+      EsVersion::EsNext => ast::EsVersion::EsNext,
+    }
+  }
+}
+
 #[derive(Debug)]
 pub struct Bailout {
   pub loc: SourceLocation,
@@ -414,6 +502,16 @@ macro_rules! id {
   };
 }
 
+/// Hash the bytes of a string with the default hasher, for generating stable names
+#[macro_export]
+macro_rules! hash {
+  ($str:expr) => {{
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    std::hash::Hasher::write(&mut hasher, $str.as_bytes());
+    std::hash::Hasher::finish(&hasher)
+  }};
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ErrorBuffer(
   std::sync::Arc<parking_lot::Mutex<Vec<swc_core::common::errors::Diagnostic>>>,
@@ -0,0 +1,68 @@
+use swc_core::common::sync::Lrc;
+use swc_core::common::BytePos;
+use swc_core::common::SourceMap;
+use swc_core::common::SourceMapper;
+use swc_core::common::Span;
+use swc_core::ecma::ast::VarDecl;
+use swc_core::ecma::ast::VarDeclKind;
+use swc_core::ecma::visit::Visit;
+use swc_core::ecma::visit::VisitWith;
+
+use crate::utils::CodeHighlight;
+use crate::utils::Diagnostic;
+use crate::utils::DiagnosticSeverity;
+use crate::utils::SourceLocation;
+
+/// Reports a warning for each `var` declaration, for projects that only allow `let` and `const`
+///
+/// Declarations that do not appear in the source code, such as those generated for TypeScript
+/// enums, are not reported.
+pub struct VarChecker<'a> {
+  diagnostics: &'a mut Vec<Diagnostic>,
+  source_map: Lrc<SourceMap>,
+}
+
+impl<'a> VarChecker<'a> {
+  pub fn new(diagnostics: &'a mut Vec<Diagnostic>, source_map: Lrc<SourceMap>) -> Self {
+    Self {
+      diagnostics,
+      source_map,
+    }
+  }
+}
+
+impl Visit for VarChecker<'_> {
+  fn visit_var_decl(&mut self, node: &VarDecl) {
+    node.visit_children_with(self);
+
+    if node.kind != VarDeclKind::Var || node.declare {
+      return;
+    }
+
+    let is_in_source = self
+      .source_map
+      .span_to_snippet(node.span)
+      .is_ok_and(|snippet| snippet.starts_with("var"));
+
+    if !is_in_source {
+      return;
+    }
+
+    let keyword_span = Span {
+      hi: node.span.lo + BytePos("var".len() as u32),
+      ..node.span
+    };
+
+    self.diagnostics.push(Diagnostic {
+      message: "Unexpected `var` declaration.".to_string(),
+      code_highlights: Some(vec![CodeHighlight {
+        message: None,
+        loc: SourceLocation::from(&self.source_map, keyword_span),
+      }]),
+      hints: Some(vec![String::from("Use `let` or `const` instead.")]),
+      show_environment: false,
+      severity: DiagnosticSeverity::Warning,
+      documentation_url: None,
+    });
+  }
+}
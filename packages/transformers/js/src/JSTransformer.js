@@ -459,6 +459,25 @@ export default (new Transformer({
       is_swc_helpers: /@swc[/\\]helpers/.test(asset.filePath),
       standalone: asset.query.has('standalone'),
       inline_constants: config.inlineConstants,
+      // Minification happens in optimizer plugins once bundles are packaged
+      minify: false,
+      keep_names: false,
+      i18n_functions: [],
+      strict_dynamic_imports: false,
+      output_indent: 0,
+      preserve_jsx: false,
+      annotate_pure: false,
+      follow_fetch_urls: false,
+      target_es_version: null,
+      reserved_globals: [],
+      ban_var: false,
+      source_map_include_content: false,
+      external_helpers: false,
+      interop: 'babel',
+      extract_css_in_js: false,
+      obfuscate_exports: null,
+      fold_environment_checks: false,
+      context_dynamic_imports: false,
       callMacro: asset.isSource
         ? async (err, src, exportName, args, loc) => {
             let mod;